@@ -1,23 +1,56 @@
-use near_sdk::{env, require};
+use near_sdk::{env, AccountId};
 
-use crate::Contract;
+use crate::errors::{ensure, ContractError};
+use crate::{types::Role, Contract};
 
 impl Contract {
     pub(crate) fn assert_agent_is_calling(&self) {
-        self.require_worker_has_valid_codehash();
+        self.require_worker_has_valid_codehash(&env::predecessor_account_id());
     }
 
-    fn require_worker_has_valid_codehash(&self) {
-        // let worker = self.get_worker(env::predecessor_account_id());
-        // require!(self.approved_codehashes.contains(&worker.codehash));
-        // TODO: Uncomment above after testing
+    // @dev calls for the active session are only accepted from the worker that owns it
+    pub(crate) fn assert_session_worker_is_calling(&self) {
+        self.assert_agent_is_calling();
+        ensure!(
+            env::predecessor_account_id() == self.get_active_session().worker,
+            ContractError::SessionBoundToAnotherWorker
+        );
+    }
+
+    pub(crate) fn require_worker_has_valid_codehash(&self, account_id: &AccountId) {
+        if !self.enforce_worker_codehash {
+            return;
+        }
+        let worker = self.get_worker(account_id.clone());
+        ensure!(
+            self.approved_codehashes.contains(&worker.codehash),
+            ContractError::WorkerCodehashNotApproved
+        );
     }
 
     pub(crate) fn require_owner(&self) {
-        require!(env::predecessor_account_id() == self.owner_id);
+        ensure!(
+            env::predecessor_account_id() == self.owner_id,
+            ContractError::Unauthorized
+        );
+    }
+
+    // @dev the owner holds every role
+    pub(crate) fn require_role(&self, role: Role) {
+        let caller = env::predecessor_account_id();
+        if caller == self.owner_id {
+            return;
+        }
+        ensure!(
+            self.has_role(caller, role),
+            ContractError::MissingRole(role)
+        );
     }
 
     pub(crate) fn require_approved_codehash(&self, codehash: &String) {
-        require!(self.approved_codehashes.contains(codehash));
+        ensure!(
+            self.approved_codehashes.contains(codehash),
+            ContractError::CodehashNotApproved
+        );
     }
 }
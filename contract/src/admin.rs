@@ -1,22 +1,389 @@
-use near_sdk::{near, require};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near, Promise};
 
-use crate::types::ChainConfig;
+use crate::constants::{MIN_FEE_BUMP_BPS, SIGNATURE_DEPOSIT_QUERY_GAS};
+use crate::derivation;
+use crate::errors::{ensure, ContractError};
+use crate::events::emit_event;
+use crate::external::{mpc_contract, this_contract};
+use crate::types::{ChainConfig, ChainId, ConfigChange, ConfigUpdate, Role, SignerConfig};
 use crate::{Contract, ContractExt};
 
 #[near]
 impl Contract {
-    pub fn add_supported_chain(&mut self, new_chain: ChainConfig) {
-        self.require_owner();
-        require!(
+    // @dev config changes are queued, see execute_config_change
+    pub fn add_supported_chain(&mut self, new_chain: ChainConfig) -> u64 {
+        self.require_role(Role::ConfigManager);
+        ensure!(
             !self.supported_chains.contains(&new_chain.chain_id),
-            "Chain already supported"
+            ContractError::ChainAlreadySupported
         );
-        self.supported_chains.push(new_chain.chain_id.clone());
-        self.config.insert(new_chain.chain_id, new_chain.config);
+        new_chain.config.validate(false);
+        self.queue_config_change(ConfigChange::AddChain {
+            chain_id: new_chain.chain_id,
+            config: new_chain.config,
+        })
     }
 
-    pub fn approve_codehash(&mut self, codehash: String) {
+    pub fn update_chain_config(&mut self, chain_id: ChainId, update: ConfigUpdate) -> u64 {
+        self.require_role(Role::ConfigManager);
+        ensure!(
+            self.config.contains_key(&chain_id),
+            ContractError::ChainNotConfigured
+        );
+        self.assert_chain_not_in_active_session(chain_id);
+        self.updated_chain_config(chain_id, update.clone());
+
+        self.queue_config_change(ConfigChange::UpdateChain { chain_id, update })
+    }
+
+    pub fn disable_chain(&mut self, chain_id: ChainId) {
+        self.require_role(Role::ConfigManager);
+        self.is_chain_supported(&chain_id);
+        self.assert_chain_not_in_active_session(chain_id);
+        self.disabled_chains.insert(chain_id);
+
+        emit_event("chain_disabled", json!({ "chain_id": chain_id }));
+    }
+
+    pub fn enable_chain(&mut self, chain_id: ChainId) {
+        self.require_role(Role::ConfigManager);
+        ensure!(
+            self.disabled_chains.remove(&chain_id),
+            ContractError::ChainNotDisabled
+        );
+
+        emit_event("chain_enabled", json!({ "chain_id": chain_id }));
+    }
+
+    pub fn remove_chain(&mut self, chain_id: ChainId) {
+        self.require_role(Role::ConfigManager);
+        self.is_chain_supported(&chain_id);
+        ensure!(
+            chain_id != self.source_chain,
+            ContractError::CannotRemoveSourceChain
+        );
+        self.assert_chain_not_in_active_session(chain_id);
+
+        self.supported_chains.retain(|c| *c != chain_id);
+        self.config.remove(&chain_id);
+        self.disabled_chains.remove(&chain_id);
+        self.cancel_chain_updates(chain_id);
+
+        emit_event("chain_removed", json!({ "chain_id": chain_id }));
+    }
+
+    pub fn set_session_timeout(&mut self, timeout_ms: u64) {
+        self.require_role(Role::ConfigManager);
+        ensure!(timeout_ms > 0, ContractError::InvalidSessionTimeout);
+        self.session_timeout_ms = timeout_ms;
+    }
+
+    pub fn set_next_nonce(&mut self, chain_id: ChainId, nonce: u64) {
+        self.require_role(Role::Operator);
+        self.is_chain_supported(&chain_id);
+        self.next_evm_nonce_by_chain.insert(chain_id, nonce);
+    }
+
+    pub fn set_fee_bump_ceiling(&mut self, ceiling_bps: u128) {
+        self.require_role(Role::ConfigManager);
+        ensure!(
+            ceiling_bps >= MIN_FEE_BUMP_BPS,
+            ContractError::InvalidFeeBumpCeiling
+        );
+        self.fee_bump_ceiling_bps = ceiling_bps;
+    }
+
+    pub fn set_require_step_confirmations(&mut self, required: bool) {
+        self.require_role(Role::ConfigManager);
+        self.require_step_confirmations = required;
+    }
+
+    pub fn set_enforce_worker_codehash(&mut self, enforce: bool) {
         self.require_owner();
+        self.enforce_worker_codehash = enforce;
+    }
+
+    pub fn approve_codehash(&mut self, codehash: String) {
+        self.require_role(Role::CodehashApprover);
         self.approved_codehashes.insert(codehash);
     }
+
+    // @dev a new path or key version derives another agent address, so it is refused mid-session
+    pub fn set_signer_config(&mut self, signer: SignerConfig) {
+        self.require_owner();
+        ensure!(
+            self.active_session.is_none(),
+            ContractError::AnotherActionInProgress
+        );
+        derivation::root_public_key_to_point(&signer.root_public_key);
+
+        emit_event("signer_config_updated", json!(signer));
+        self.signer = signer;
+    }
+
+    // @dev the MPC contract refunds what exceeds its requirement to this contract, not to the caller,
+    // so the deposit collected from callers follows what it reports. Anyone can sync it, the value
+    // comes from the MPC contract itself
+    pub fn sync_signature_deposit(&mut self) -> Promise {
+        mpc_contract::ext(self.signer.mpc_contract_id.clone())
+            .with_static_gas(SIGNATURE_DEPOSIT_QUERY_GAS)
+            .experimental_signature_deposit()
+            .then(
+                this_contract::ext(env::current_account_id())
+                    .with_static_gas(SIGNATURE_DEPOSIT_QUERY_GAS)
+                    .sync_signature_deposit_callback(),
+            )
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::get_created_receipts;
+    use near_sdk::NearToken;
+
+    const NEW_LENDING_POOL: &str = "0x794a61358D6845594F94dc1DB02A252b5b4814aD";
+
+    fn lending_pool_update() -> ConfigUpdate {
+        ConfigUpdate {
+            aave: Some(AaveConfigUpdate {
+                lending_pool_address: Some(NEW_LENDING_POOL.parse().unwrap()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn start_session(contract: &mut crate::Contract) {
+        set_context(WORKER);
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+        set_context(OWNER);
+    }
+
+    #[test]
+    fn test_update_chain_config_keeps_other_fields() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        let before = contract
+            .get_chain_config(&DEFAULT_DESTINATION_CHAIN)
+            .clone();
+
+        let id = contract.update_chain_config(DEFAULT_DESTINATION_CHAIN, lending_pool_update());
+        set_context_with_timestamp(OWNER, contract.get_config_timelock());
+        contract.execute_config_change(id);
+
+        let after = contract.get_chain_config(&DEFAULT_DESTINATION_CHAIN);
+        assert_eq!(
+            after.aave.lending_pool_address.to_string(),
+            NEW_LENDING_POOL
+        );
+        assert_eq!(after.aave.asset, before.aave.asset);
+        assert_eq!(after.cctp.messenger_address, before.cctp.messenger_address);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain used by the active session")]
+    fn fails_to_update_chain_of_active_session() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_worker();
+        start_session(&mut contract);
+
+        contract.update_chain_config(DEFAULT_DESTINATION_CHAIN, lending_pool_update());
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain disabled")]
+    fn fails_to_start_rebalance_on_disabled_chain() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_worker();
+        contract.disable_chain(DEFAULT_DESTINATION_CHAIN);
+
+        start_session(&mut contract);
+    }
+
+    #[test]
+    fn test_enable_chain() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_worker();
+        contract.disable_chain(DEFAULT_DESTINATION_CHAIN);
+        assert_eq!(
+            contract.get_disabled_chains(),
+            vec![DEFAULT_DESTINATION_CHAIN]
+        );
+
+        contract.enable_chain(DEFAULT_DESTINATION_CHAIN);
+        assert!(contract.get_disabled_chains().is_empty());
+
+        start_session(&mut contract);
+    }
+
+    #[test]
+    fn test_remove_chain() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.remove_chain(DEFAULT_DESTINATION_CHAIN);
+
+        assert_eq!(contract.get_supported_chains(), vec![DEFAULT_SOURCE_CHAIN]);
+        assert!(!contract.config.contains_key(&DEFAULT_DESTINATION_CHAIN));
+    }
+
+    #[test]
+    fn test_remove_chain_cancels_its_pending_updates() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.update_chain_config(DEFAULT_DESTINATION_CHAIN, ConfigUpdate::default());
+        let kept = contract.update_chain_config(DEFAULT_SOURCE_CHAIN, ConfigUpdate::default());
+
+        contract.remove_chain(DEFAULT_DESTINATION_CHAIN);
+
+        let pending = contract.get_pending_config_changes();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, kept);
+    }
+
+    fn new_chain() -> ChainConfig {
+        build_fake_configs().remove(1).with_chain_id("3")
+    }
+
+    #[test]
+    fn test_add_supported_chain_is_queued() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        let id = contract.add_supported_chain(new_chain());
+
+        assert_eq!(contract.get_pending_config_changes()[0].id, id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid lending pool")]
+    fn fails_to_add_chain_with_zero_lending_pool() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        let mut chain = new_chain();
+        chain.config.aave.lending_pool_address = EvmAddress::ZERO;
+        contract.add_supported_chain(chain);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid vault")]
+    fn fails_to_add_chain_with_vault() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        let chain = new_chain().with_config(build_fake_configs().remove(0).config);
+        contract.add_supported_chain(chain);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid vault")]
+    fn fails_to_clear_source_chain_vault() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.update_chain_config(
+            DEFAULT_SOURCE_CHAIN,
+            ConfigUpdate {
+                rebalancer: Some(RebalancerConfigUpdate {
+                    vault_address: Some(EvmAddress::ZERO),
+                }),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn sandbox_signer() -> SignerConfig {
+        SignerConfig {
+            mpc_contract_id: "signer.test.near".parse().unwrap(),
+            path: "ethereum-2".to_string(),
+            key_version: 1,
+            root_public_key: "secp256k1:4NfTiv3UsGahebgTaHyD9vF8KYKMBnfd6kh94mK6xv8fGBiJB8TBtFMP5WWXz6B89Ac1fbpzPwAvoyQebemHFwx3"
+                .parse()
+                .unwrap(),
+            request_format: SignRequestFormat::V2 {
+                ecdsa_domain_id: 0,
+                eddsa_domain_id: 1,
+            },
+            signature_deposit: NearToken::from_yoctonear(1),
+        }
+    }
+
+    #[test]
+    fn test_set_signer_config() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.set_signer_config(sandbox_signer());
+
+        assert_eq!(contract.get_signer_config(), sandbox_signer());
+        assert_eq!(
+            contract.get_agent_address(),
+            crate::derivation::derive_evm_address(
+                &sandbox_signer().root_public_key,
+                &near_sdk::env::current_account_id(),
+                "ethereum-2",
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Another action in progress")]
+    fn fails_to_set_signer_config_during_session() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_worker();
+        start_session(&mut contract);
+        contract.set_signer_config(sandbox_signer());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn fails_to_set_signer_config_if_not_owner() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        set_context(WORKER);
+        contract.set_signer_config(sandbox_signer());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot remove the source chain")]
+    fn fails_to_remove_source_chain() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.remove_chain(DEFAULT_SOURCE_CHAIN);
+    }
+
+    #[test]
+    fn test_sync_signature_deposit_queries_the_mpc_contract() {
+        set_context(OWNER);
+        let mut contract = init_contract_with_defaults();
+
+        set_context("random.near");
+        contract.sync_signature_deposit();
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, contract.signer.mpc_contract_id);
+        assert!(receipts[0].actions.iter().any(|action| matches!(
+            action,
+            MockAction::FunctionCallWeight { method_name, .. }
+                if method_name == b"experimental_signature_deposit"
+        )));
+    }
 }
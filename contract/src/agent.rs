@@ -4,6 +4,7 @@ use dcap_qvl::verify;
 use hex::{decode, encode};
 
 use crate::collateral;
+use crate::errors::{ContractError, OrContractError};
 use crate::types::Worker;
 use crate::{Contract, ContractExt};
 
@@ -17,10 +18,15 @@ impl Contract {
         tcb_info: String,
     ) -> bool {
         let collateral = collateral::get_collateral(collateral);
-        let quote = decode(quote_hex).unwrap();
+        let quote = decode(quote_hex).or_error(ContractError::InvalidQuote);
         let now = env::block_timestamp() / 1000000000;
-        let result = verify::verify(&quote, &collateral, now).expect("report is not verified");
-        let rtmr3 = encode(result.report.as_td10().unwrap().rt_mr3.to_vec());
+        let result =
+            verify::verify(&quote, &collateral, now).or_error(ContractError::QuoteNotVerified);
+        let report = result
+            .report
+            .as_td10()
+            .or_error(ContractError::InvalidQuote);
+        let rtmr3 = encode(report.rt_mr3.to_vec());
         let codehash = collateral::verify_codehash(tcb_info, rtmr3);
 
         // Only allow workers to register if their codehash is approved
@@ -1,43 +1,49 @@
-use std::str::FromStr;
-
+use crate::errors::{ensure, ContractError};
 use crate::{
-    constants::*, ecdsa, external::this_contract, tx_builders, types::ApproveAaveSupplyArgs,
+    tx_builders,
+    types::{ApproveAaveSupplyArgs, Step},
     Contract, ContractExt,
 };
-use alloy_primitives::Address;
-use near_sdk::{env, near, Gas, Promise};
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_aave_approve_supply_tx(
         &mut self,
         args: ApproveAaveSupplyArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_aave_approve_supply_tx(args);
+
+        self.trigger_signature(Step::AaveApproveSupply, tx, callback_gas_tgas)
+    }
+}
 
-        assert!(args.chain_id != self.source_chain); // @dev since Aave interaction in the source chain is via the Vault contract
+impl Contract {
+    pub(crate) fn prepare_aave_approve_supply_tx(
+        &self,
+        args: ApproveAaveSupplyArgs,
+    ) -> EVMTransaction {
+        self.assert_step_amount(Step::AaveApproveSupply, args.amount);
 
-        let config = self.get_chain_config(&args.chain_id);
+        ensure!(
+            self.get_chain_id_from_the_step_and_current_session(Step::AaveApproveSupply)
+                != self.source_chain,
+            ContractError::AaveOnSourceChain
+        ); // @dev since Aave interaction in the source chain is via the Vault contract
+
+        let cfg = self.get_chain_config_from_step_and_current_session(Step::AaveApproveSupply);
 
         let mut tx = args.clone().partial_transaction;
         tx.input = tx_builders::build_aave_approve_supply_tx(
             args.amount,
-            config.aave.lending_pool_address.clone(),
-        );
-        tx.to = Some(
-            Address::from_str(&config.cctp.usdc_address)
-                .expect("Invalid USDC address")
-                .into_array(),
+            cfg.aave.lending_pool_address.into(),
         );
-
-        let payload_hash = self.hash_payload(&tx);
-
-        ecdsa::get_sig(payload_hash, PATH.to_string(), KEY_VERSION).then(
-            this_contract::ext(env::current_account_id())
-                .with_static_gas(Gas::from_tgas(callback_gas_tgas))
-                .sign_generic_callback(tx),
-        )
+        tx.to = Some(cfg.cctp.usdc_address.into_array());
+        tx
     }
 }
 
@@ -51,41 +57,82 @@ mod maintests {
     use omni_transaction::evm::EVMTransaction;
 
     const DEFAULT_TGAS: u64 = 10;
-    const DEFAULT_AMOUNT: u128 = 1_000_000_000u128;
+    const MAX_FEE: u128 = 1_000;
+
+    fn start_session_after_mint() -> crate::Contract {
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[
+                Step::AaveWithdraw,
+                Step::CCTPApproveBurn,
+                Step::CCTPBurn,
+                Step::CCTPMint,
+            ],
+        );
+        contract.active_session.as_mut().unwrap().cctp_max_fee = Some(MAX_FEE);
+        contract
+    }
 
-    // TODO: Restore once access control is working
-    // #[test]
-    // #[should_panic]
-    // fn fails_if_not_agent() {
-    //     set_context(OWNER);
+    #[test]
+    #[should_panic(expected = "Worker not registered")]
+    fn fails_if_not_agent() {
+        set_context(WORKER);
 
-    //     let mut contract = init_contract_with_defaults();
+        let mut contract = start_session_after_mint();
 
-    //     // No-agent
-    //     set_context("random.near");
+        // No-agent
+        set_context("random.near");
 
-    //     let args = build_args();
-    //     contract.build_and_sign_aave_approve_supply_tx(args, DEFAULT_TGAS);
-    // }
+        let args = build_args(DEFAULT_AMOUNT);
+        contract.build_and_sign_aave_approve_supply_tx(args, DEFAULT_TGAS);
+    }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "Aave on the source chain is reached through the vault")]
     fn fails_if_chain_is_source_chain() {
-        set_context(OWNER);
+        set_context(WORKER);
 
-        let mut contract = init_contract_with_defaults();
+        let (mut contract, nonce) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        // @dev start_rebalance rejects this, but keep the guard for sessions that predate it
+        contract.logs.get_mut(&nonce).unwrap().destination_chain = DEFAULT_SOURCE_CHAIN;
 
-        let mut args = build_args();
-        args.chain_id = contract.source_chain; // @dev this should fail
+        let mut args = build_args(DEFAULT_AMOUNT);
+        args.partial_transaction.chain_id = contract.source_chain;
 
         contract.build_and_sign_aave_approve_supply_tx(args, DEFAULT_TGAS);
     }
 
+    #[test]
+    #[should_panic(expected = "Wrong step for current position")]
+    fn fails_if_mint_is_not_signed() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn, Step::CCTPBurn],
+        );
+
+        contract.build_and_sign_aave_approve_supply_tx(build_args(DEFAULT_AMOUNT), DEFAULT_TGAS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount out of bounds for session")]
+    fn fails_if_amount_above_session_amount() {
+        set_context(WORKER);
+
+        let mut contract = start_session_after_mint();
+
+        contract
+            .build_and_sign_aave_approve_supply_tx(build_args(DEFAULT_AMOUNT + 1), DEFAULT_TGAS);
+    }
+
     #[test]
     fn test_build_and_sign_aave_approve_supply_tx() {
-        let mut contract = init_contract_with_defaults();
+        set_context(WORKER);
 
-        let args = build_args();
+        let mut contract = start_session_after_mint();
+
+        let args = build_args(DEFAULT_AMOUNT - MAX_FEE);
 
         contract.build_and_sign_aave_approve_supply_tx(args, DEFAULT_TGAS);
 
@@ -112,14 +159,12 @@ mod maintests {
                         let method =
                             String::from_utf8(method_name).expect("method_name is not utf8");
 
-                        if method == "sign_generic_callback" {
+                        if method == "sign_callback" {
                             found = true;
 
                             // valid asserts
                             assert!(prepaid_gas > Gas::from_tgas(0));
                             assert!(attached_deposit.is_zero());
-
-                            // TODO: continue asserts
                         }
                     }
                     _ => {}
@@ -127,12 +172,16 @@ mod maintests {
             }
         }
 
-        assert!(found, "sign_generic_callback not found");
+        assert!(found, "sign_callback not found");
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_DESTINATION_CHAIN),
+            Some(build_partial_tx().nonce + 1)
+        );
     }
 
     fn build_partial_tx() -> EVMTransaction {
         EVMTransaction {
-            chain_id: 1,
+            chain_id: DEFAULT_DESTINATION_CHAIN,
             nonce: 1,
             to: None,
             input: vec![],
@@ -144,10 +193,9 @@ mod maintests {
         }
     }
 
-    fn build_args() -> ApproveAaveSupplyArgs {
+    fn build_args(amount: u128) -> ApproveAaveSupplyArgs {
         ApproveAaveSupplyArgs {
-            chain_id: DEFAULT_DESTINATION_CHAIN, // @dev cannot supply to AAVE in source chain
-            amount: DEFAULT_AMOUNT,
+            amount,
             partial_transaction: build_partial_tx(),
         }
     }
@@ -1,41 +1,37 @@
-use std::str::FromStr;
-
 use crate::{
-    constants::*, ecdsa, external::this_contract, tx_builders, types::ApproveCctpBurnArgs,
+    tx_builders,
+    types::{ApproveCctpBurnArgs, Step},
     Contract, ContractExt,
 };
-use alloy_primitives::Address;
-use near_sdk::{env, near, Gas, Promise};
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_cctp_approve_burn_tx(
         &mut self,
         args: ApproveCctpBurnArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_cctp_approve_burn_tx(args);
 
-        let config = self.get_chain_config(&args.chain_id);
+        self.trigger_signature(Step::CCTPApproveBurn, tx, callback_gas_tgas)
+    }
+}
 
-        let mut tx = args.clone().partial_transaction;
-        tx.input = tx_builders::build_cctp_approve_burn_tx(
-            args.amount,
-            config.cctp.messenger_address.clone(),
-        );
-        tx.to = Some(
-            Address::from_str(&config.cctp.usdc_address)
-                .expect("Invalid USDC address")
-                .into_array(),
-        );
+impl Contract {
+    pub(crate) fn prepare_cctp_approve_burn_tx(&self, args: ApproveCctpBurnArgs) -> EVMTransaction {
+        self.assert_step_amount(Step::CCTPApproveBurn, args.amount);
 
-        let payload_hash = self.hash_payload(&tx);
+        let cfg = self.get_chain_config_from_step_and_current_session(Step::CCTPApproveBurn);
 
-        ecdsa::get_sig(payload_hash, PATH.to_string(), KEY_VERSION).then(
-            this_contract::ext(env::current_account_id())
-                .with_static_gas(Gas::from_tgas(callback_gas_tgas))
-                .sign_generic_callback(tx),
-        )
+        let mut tx = args.clone().partial_transaction;
+        tx.input =
+            tx_builders::build_cctp_approve_burn_tx(args.amount, cfg.cctp.messenger_address.into());
+        tx.to = Some(cfg.cctp.usdc_address.into_array());
+        tx
     }
 }
 
@@ -43,5 +39,47 @@ impl Contract {
 mod maintests {
     use crate::test_helpers::*;
     use crate::types::*;
-    use near_sdk::env;
+
+    fn build_args(amount: u128) -> ApproveCctpBurnArgs {
+        ApproveCctpBurnArgs {
+            amount,
+            partial_transaction: build_partial_tx(),
+        }
+    }
+
+    #[test]
+    fn test_approve_burn_reserves_nonce() {
+        set_context(WORKER);
+
+        let (mut contract, _) =
+            start_session_with_signed_steps(Flow::AaveToAave, &[Step::AaveWithdraw]);
+
+        contract.build_and_sign_cctp_approve_burn_tx(build_args(DEFAULT_AMOUNT), 10);
+
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_partial_tx().nonce + 1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong step for current position")]
+    fn fails_if_withdraw_is_not_signed() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+
+        contract.build_and_sign_cctp_approve_burn_tx(build_args(DEFAULT_AMOUNT), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount does not match session")]
+    fn fails_if_amount_exceeds_session_amount() {
+        set_context(WORKER);
+
+        let (mut contract, _) =
+            start_session_with_signed_steps(Flow::AaveToAave, &[Step::AaveWithdraw]);
+
+        contract.build_and_sign_cctp_approve_burn_tx(build_args(DEFAULT_AMOUNT + 1), 10);
+    }
 }
@@ -1,38 +1,43 @@
-use std::str::FromStr;
-
-use crate::{constants::*, ecdsa, external::this_contract, tx_builders, Contract, ContractExt};
-use alloy_primitives::Address;
-use near_sdk::{env, near, Gas, Promise};
+use crate::{
+    tx_builders,
+    types::{ApproveRebalancerDepositArgs, Step},
+    Contract, ContractExt,
+};
+use near_sdk::{near, PromiseOrValue};
 use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_approve_vault_to_manage_agents_usdc_tx(
         &mut self,
-        partial_transaction: EVMTransaction,
+        args: ApproveRebalancerDepositArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_approve_vault_to_manage_agents_usdc_tx(args);
 
-        let config = self.get_chain_config(&self.source_chain);
+        self.trigger_signature(Step::RebalancerApproveDeposit, tx, callback_gas_tgas)
+    }
+}
 
-        let mut tx = partial_transaction;
-        tx.input = tx_builders::build_approve_vault_to_manage_agents_usdc_tx(
-            config.rebalancer.vault_address.clone(),
-        );
-        tx.to = Some(
-            Address::from_str(&config.cctp.usdc_address)
-                .expect("Invalid USDC address")
-                .into_array(),
-        );
+impl Contract {
+    pub(crate) fn prepare_approve_vault_to_manage_agents_usdc_tx(
+        &self,
+        args: ApproveRebalancerDepositArgs,
+    ) -> EVMTransaction {
+        self.assert_step_amount(Step::RebalancerApproveDeposit, args.amount);
 
-        let payload_hash = self.hash_payload(&tx);
+        let cfg =
+            self.get_chain_config_from_step_and_current_session(Step::RebalancerApproveDeposit);
 
-        ecdsa::get_sig(payload_hash, PATH.to_string(), KEY_VERSION).then(
-            this_contract::ext(env::current_account_id())
-                .with_static_gas(Gas::from_tgas(callback_gas_tgas))
-                .sign_generic_callback(tx),
-        )
+        let mut tx = args.clone().partial_transaction;
+        tx.input = tx_builders::build_approve_vault_to_manage_agents_usdc_tx(
+            args.amount,
+            cfg.rebalancer.vault_address.into(),
+        );
+        tx.to = Some(cfg.cctp.usdc_address.into_array());
+        tx
     }
 }
 
@@ -40,5 +45,68 @@ impl Contract {
 mod maintests {
     use crate::test_helpers::*;
     use crate::types::*;
-    use near_sdk::env;
+
+    fn build_args(amount: u128) -> ApproveRebalancerDepositArgs {
+        let mut partial_transaction = build_partial_tx();
+        partial_transaction.chain_id = DEFAULT_SOURCE_CHAIN;
+
+        ApproveRebalancerDepositArgs {
+            amount,
+            partial_transaction,
+        }
+    }
+
+    #[test]
+    fn test_unwind_approves_vault_on_source_chain() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::RebalancerToAave,
+            &[Step::RebalancerWithdrawToAllocate],
+        );
+        contract.active_session.as_mut().unwrap().unwinding = true;
+
+        contract
+            .build_and_sign_approve_vault_to_manage_agents_usdc_tx(build_args(DEFAULT_AMOUNT), 10);
+
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_partial_tx().nonce + 1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong step for current position")]
+    fn fails_if_mint_is_not_signed() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToRebalancer,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn, Step::CCTPBurn],
+        );
+
+        contract
+            .build_and_sign_approve_vault_to_manage_agents_usdc_tx(build_args(DEFAULT_AMOUNT), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount out of bounds for session")]
+    fn fails_if_amount_above_session_amount() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToRebalancer,
+            &[
+                Step::AaveWithdraw,
+                Step::CCTPApproveBurn,
+                Step::CCTPBurn,
+                Step::CCTPMint,
+            ],
+        );
+
+        contract.build_and_sign_approve_vault_to_manage_agents_usdc_tx(
+            build_args(DEFAULT_AMOUNT + 1),
+            10,
+        );
+    }
 }
@@ -0,0 +1,234 @@
+use crate::errors::{ensure, ContractError};
+use crate::{
+    constants::MAX_BATCH_SIZE,
+    ecdsa,
+    external::this_contract,
+    types::{BatchEntry, BatchRequest, SignatureDeposit, Step},
+    Contract, ContractExt,
+};
+use near_sdk::{env, near, Gas, Promise};
+use omni_transaction::evm::EVMTransaction;
+
+#[near]
+impl Contract {
+    // @dev signs several transactions with one MPC round-trip. Steps have to follow the flow
+    // sequence from the next unsigned one, as if each previous request was already signed
+    #[payable]
+    pub fn build_and_sign_batch(
+        &mut self,
+        requests: Vec<BatchRequest>,
+        callback_gas_tgas: u64,
+    ) -> Promise {
+        self.assert_session_worker_is_calling();
+        self.assert_session_not_expired();
+        self.assert_flow_not_paused(&self.get_active_session().flow);
+        ensure!(!requests.is_empty(), ContractError::EmptyBatch);
+        ensure!(
+            requests.len() <= MAX_BATCH_SIZE,
+            ContractError::BatchTooLarge
+        );
+
+        let deposit = SignatureDeposit::collect(
+            self.signer
+                .signature_deposit
+                .saturating_mul(requests.len() as u128),
+        );
+        let nonce = self.get_active_session().nonce;
+        let unsigned_steps = self.unsigned_steps();
+
+        let mut entries = Vec::with_capacity(requests.len());
+        let mut signatures: Option<Promise> = None;
+        for (position, request) in requests.into_iter().enumerate() {
+            let (step, tx) = self.prepare_batch_request(request);
+
+            if position == 0 {
+                self.assert_step_is_next(step);
+            } else {
+                // @dev a step cannot be confirmed before the batch signing it returns
+                ensure!(
+                    !self.require_step_confirmations,
+                    ContractError::PreviousStepNotConfirmed
+                );
+                ensure!(
+                    unsigned_steps.get(position) == Some(&step),
+                    ContractError::WrongStep
+                );
+            }
+            self.assert_valid_partial_transaction(
+                &tx,
+                self.get_chain_id_from_the_step_and_current_session(step),
+            );
+            self.assert_chain_not_paused(tx.chain_id);
+            self.reserve_evm_nonce(tx.chain_id, tx.nonce, false);
+
+            let signature = ecdsa::get_sig(self.hash_payload(&tx), &self.signer);
+            signatures = Some(match signatures {
+                Some(promise) => promise.and(signature),
+                None => signature,
+            });
+            entries.push(BatchEntry {
+                step,
+                ethereum_tx: tx,
+            });
+        }
+
+        signatures.unwrap().then(
+            this_contract::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(callback_gas_tgas))
+                .sign_batch_callback(nonce, entries, deposit),
+        )
+    }
+}
+
+impl Contract {
+    fn prepare_batch_request(&mut self, request: BatchRequest) -> (Step, EVMTransaction) {
+        match request {
+            BatchRequest::AaveWithdraw(args) => {
+                (Step::AaveWithdraw, self.prepare_aave_withdraw_tx(args))
+            }
+            BatchRequest::RebalancerWithdrawToAllocate(args) => (
+                Step::RebalancerWithdrawToAllocate,
+                self.prepare_withdraw_for_crosschain_allocation_tx(args),
+            ),
+            BatchRequest::CCTPApproveBurn(args) => (
+                Step::CCTPApproveBurn,
+                self.prepare_cctp_approve_burn_tx(args),
+            ),
+            BatchRequest::CCTPBurn(args) => (Step::CCTPBurn, self.prepare_cctp_burn_tx(args)),
+            BatchRequest::CCTPMint(args) => (Step::CCTPMint, self.prepare_cctp_mint_tx(args)),
+            BatchRequest::AaveApproveSupply(args) => (
+                Step::AaveApproveSupply,
+                self.prepare_aave_approve_supply_tx(args),
+            ),
+            BatchRequest::AaveSupply(args) => (Step::AaveSupply, self.prepare_aave_supply_tx(args)),
+            BatchRequest::RebalancerApproveDeposit(args) => (
+                Step::RebalancerApproveDeposit,
+                self.prepare_approve_vault_to_manage_agents_usdc_tx(args),
+            ),
+            BatchRequest::RebalancerDeposit(args) => {
+                (Step::RebalancerDeposit, self.prepare_return_funds_tx(args))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::constants::DEFAULT_SIGNATURE_DEPOSIT;
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::get_created_receipts;
+    use near_sdk::NearToken;
+
+    const DEFAULT_TGAS: u64 = 10;
+
+    fn build_withdraw_request() -> BatchRequest {
+        BatchRequest::AaveWithdraw(AaveArgs {
+            amount: DEFAULT_AMOUNT,
+            partial_transaction: build_partial_tx(),
+        })
+    }
+
+    fn build_approve_burn_request() -> BatchRequest {
+        let mut partial_transaction = build_partial_tx();
+        partial_transaction.nonce += 1;
+
+        BatchRequest::CCTPApproveBurn(ApproveCctpBurnArgs {
+            amount: DEFAULT_AMOUNT,
+            partial_transaction,
+        })
+    }
+
+    fn created_method_names() -> Vec<String> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::FunctionCallWeight { method_name, .. } => {
+                    Some(String::from_utf8(method_name).unwrap())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_fans_out_sign_requests() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        set_context_with_attached_deposit(WORKER, DEFAULT_SIGNATURE_DEPOSIT.saturating_mul(2));
+
+        contract.build_and_sign_batch(
+            vec![build_withdraw_request(), build_approve_burn_request()],
+            DEFAULT_TGAS,
+        );
+
+        let methods = created_method_names();
+        assert_eq!(methods.iter().filter(|m| *m == "sign").count(), 2);
+        assert_eq!(
+            methods
+                .iter()
+                .filter(|m| *m == "sign_batch_callback")
+                .count(),
+            1
+        );
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_partial_tx().nonce + 2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Batch is empty")]
+    fn fails_if_batch_is_empty() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+
+        contract.build_and_sign_batch(vec![], DEFAULT_TGAS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Batch exceeds")]
+    fn fails_if_batch_is_too_large() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+
+        contract.build_and_sign_batch(
+            vec![build_withdraw_request(); crate::constants::MAX_BATCH_SIZE + 1],
+            DEFAULT_TGAS,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong step for current position")]
+    fn fails_if_steps_are_out_of_order() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        set_context_with_attached_deposit(WORKER, DEFAULT_SIGNATURE_DEPOSIT.saturating_mul(2));
+
+        // @dev the burn comes after the withdraw, not twice the withdraw
+        contract.build_and_sign_batch(
+            vec![build_withdraw_request(), build_withdraw_request()],
+            DEFAULT_TGAS,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit does not cover the signature deposit")]
+    fn fails_if_deposit_does_not_cover_every_request() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        set_context_with_attached_deposit(WORKER, NearToken::from_yoctonear(1));
+
+        contract.build_and_sign_batch(
+            vec![build_withdraw_request(), build_approve_burn_request()],
+            DEFAULT_TGAS,
+        );
+    }
+}
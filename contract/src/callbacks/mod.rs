@@ -1,3 +1,5 @@
+pub mod sign_batch_callback;
 pub mod sign_callback;
 pub mod sign_crosschain_balance_callback;
-pub mod sign_generic_callback;
+pub mod sign_replacement_callback;
+pub mod sync_signature_deposit_callback;
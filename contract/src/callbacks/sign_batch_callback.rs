@@ -0,0 +1,277 @@
+use crate::errors::ContractError;
+use crate::{
+    ecdsa,
+    types::{BatchEntry, SignatureDeposit},
+    Contract, ContractExt,
+};
+use near_sdk::{env, near, PromiseResult};
+use omni_transaction::signer::types::SignatureResponse;
+
+#[near]
+impl Contract {
+    // @dev keeps the longest signed prefix of the batch. Entries after a failed one are dropped,
+    // a later step would be out of order and a later nonce would leave a gap on its chain. Nothing
+    // here panics, that would roll back the refund and the released nonces
+    #[private]
+    pub fn sign_batch_callback(
+        &mut self,
+        nonce: u64,
+        entries: Vec<BatchEntry>,
+        deposit: SignatureDeposit,
+    ) -> Vec<Vec<u8>> {
+        let requested = entries.len();
+        let mut succeeded_calls = 0;
+        let mut signed = Vec::with_capacity(requested);
+        let mut dropped = Vec::new();
+        // @dev a stale session fails every entry, none of them is cached
+        let session = self.check_callback_session(nonce);
+        for (index, entry) in entries.into_iter().enumerate() {
+            let payload_hash = self.hash_payload(&entry.ethereum_tx);
+            let signature = match env::promise_result(index as u64) {
+                PromiseResult::Successful(value) => {
+                    succeeded_calls += 1;
+                    near_sdk::serde_json::from_slice::<SignatureResponse>(&value)
+                        .ok()
+                        .and_then(|response| {
+                            ecdsa::verify_signature(&response, &payload_hash, &self.signer)
+                        })
+                        .ok_or_else(|| ContractError::SignatureMismatch.to_string())
+                }
+                _ => Err(format!("Batch entry {} failed", index)),
+            };
+            // @dev the order is checked again, the session may have changed since the request
+            let signature = signature.and_then(|signature| {
+                session
+                    .clone()
+                    .and_then(|_| self.check_step_is_next(entry.step))
+                    .map(|_| signature)
+                    .map_err(|error| format!("Batch entry {} discarded: {}", index, error))
+            });
+
+            match signature {
+                Ok(signature) if dropped.is_empty() => {
+                    signed.push(self.cache_step_signature(
+                        nonce,
+                        entry.step,
+                        &entry.ethereum_tx,
+                        payload_hash,
+                        &signature,
+                    ));
+                }
+                Ok(_) => dropped.push(entry.ethereum_tx),
+                Err(reason) => {
+                    env::log_str(&reason);
+                    if dropped.is_empty() && session.is_ok() {
+                        self.mark_step_signature_failed(nonce, entry.step);
+                    }
+                    dropped.push(entry.ethereum_tx);
+                }
+            }
+        }
+
+        // @dev released from the last one, so consecutive nonces on a chain all roll back
+        for tx in dropped.iter().rev() {
+            self.release_evm_nonce(tx.chain_id, tx.nonce);
+        }
+        deposit.refund_unspent(succeeded_calls, requested as u128);
+
+        signed
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::constants::DEFAULT_SIGNATURE_DEPOSIT;
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{env, serde_json, testing_env, PromiseResult};
+    use omni_transaction::evm::EVMTransaction;
+    use omni_transaction::signer::types::SignatureResponse;
+
+    fn start_session() -> (crate::Contract, u64) {
+        set_context(WORKER);
+
+        let (mut contract, nonce) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        let withdraw_tx = build_withdraw_tx();
+        contract
+            .next_evm_nonce_by_chain
+            .insert(withdraw_tx.chain_id, withdraw_tx.nonce + 2); // @dev reserved by the batch
+
+        (contract, nonce)
+    }
+
+    fn build_withdraw_tx() -> EVMTransaction {
+        build_partial_tx()
+    }
+
+    fn build_approve_tx() -> EVMTransaction {
+        let mut tx = build_partial_tx();
+        tx.nonce += 1;
+        tx
+    }
+
+    fn build_entries() -> Vec<BatchEntry> {
+        vec![
+            BatchEntry {
+                step: Step::AaveWithdraw,
+                ethereum_tx: build_withdraw_tx(),
+            },
+            BatchEntry {
+                step: Step::CCTPApproveBurn,
+                ethereum_tx: build_approve_tx(),
+            },
+        ]
+    }
+
+    fn build_batch_deposit() -> SignatureDeposit {
+        SignatureDeposit {
+            payer: WORKER.parse().unwrap(),
+            attached: DEFAULT_ATTACHED_DEPOSIT,
+            required: DEFAULT_SIGNATURE_DEPOSIT.saturating_mul(2),
+        }
+    }
+
+    fn set_promise_results(results: Vec<Option<SignatureResponse>>) {
+        let mut builder = VMContextBuilder::new();
+        builder.predecessor_account_id(env::current_account_id());
+
+        testing_env!(
+            builder.build(),
+            near_sdk::test_vm_config(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            results
+                .into_iter()
+                .map(|result| match result {
+                    Some(signature) => {
+                        PromiseResult::Successful(serde_json::to_vec(&signature).unwrap())
+                    }
+                    None => PromiseResult::Failed,
+                })
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_caches_every_signed_entry() {
+        let (mut contract, nonce) = start_session();
+        set_promise_results(vec![
+            Some(build_tx_signature(&contract, &build_withdraw_tx())),
+            Some(build_tx_signature(&contract, &build_approve_tx())),
+        ]);
+
+        let signed = contract.sign_batch_callback(nonce, build_entries(), build_batch_deposit());
+
+        assert_eq!(signed.len(), 2);
+        assert_eq!(signed[0][0], Step::AaveWithdraw as u8);
+        assert_eq!(signed[1][0], Step::CCTPApproveBurn as u8);
+        assert!(contract.has_signature(Step::AaveWithdraw));
+        assert!(contract.has_signature(Step::CCTPApproveBurn));
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_approve_tx().nonce + 1)
+        );
+        assert!(get_created_transfers().is_empty());
+    }
+
+    #[test]
+    fn test_keeps_signed_prefix_and_refunds_failed_calls() {
+        let (mut contract, nonce) = start_session();
+        set_promise_results(vec![
+            Some(build_tx_signature(&contract, &build_withdraw_tx())),
+            None,
+        ]);
+
+        let signed = contract.sign_batch_callback(nonce, build_entries(), build_batch_deposit());
+
+        assert_eq!(signed.len(), 1);
+        assert!(contract.has_signature(Step::AaveWithdraw));
+        assert!(!contract.has_signature(Step::CCTPApproveBurn));
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_approve_tx().nonce)
+        );
+        assert_eq!(
+            get_created_transfers(),
+            vec![(
+                WORKER.parse().unwrap(),
+                DEFAULT_ATTACHED_DEPOSIT.saturating_sub(DEFAULT_SIGNATURE_DEPOSIT)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_drops_entries_after_a_mismatch() {
+        let (mut contract, nonce) = start_session();
+        set_promise_results(vec![
+            Some(build_mock_signature()),
+            Some(build_tx_signature(&contract, &build_approve_tx())),
+        ]);
+
+        let signed = contract.sign_batch_callback(nonce, build_entries(), build_batch_deposit());
+
+        assert!(signed.is_empty());
+        assert!(!contract.has_signature(Step::AaveWithdraw));
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_withdraw_tx().nonce)
+        );
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Failed
+        );
+    }
+
+    #[test]
+    fn test_discards_batch_of_a_stale_session() {
+        let (mut contract, nonce) = start_session();
+        set_promise_results(vec![
+            Some(build_tx_signature(&contract, &build_withdraw_tx())),
+            Some(build_tx_signature(&contract, &build_approve_tx())),
+        ]);
+
+        let signed =
+            contract.sign_batch_callback(nonce + 1, build_entries(), build_batch_deposit());
+
+        assert!(signed.is_empty());
+        assert!(!contract.has_signature(Step::AaveWithdraw));
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_withdraw_tx().nonce)
+        );
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Started
+        );
+        assert_eq!(
+            get_created_transfers(),
+            vec![(
+                WORKER.parse().unwrap(),
+                DEFAULT_ATTACHED_DEPOSIT
+                    .saturating_sub(DEFAULT_SIGNATURE_DEPOSIT.saturating_mul(2))
+            )]
+        );
+    }
+
+    #[test]
+    fn test_drops_entries_that_are_no_longer_in_order() {
+        let (mut contract, nonce) = start_session();
+        set_context(OWNER);
+        contract.set_require_step_confirmations(true);
+        set_promise_results(vec![
+            Some(build_tx_signature(&contract, &build_withdraw_tx())),
+            Some(build_tx_signature(&contract, &build_approve_tx())),
+        ]);
+
+        let signed = contract.sign_batch_callback(nonce, build_entries(), build_batch_deposit());
+
+        assert_eq!(signed.len(), 1);
+        assert!(contract.has_signature(Step::AaveWithdraw));
+        assert!(!contract.has_signature(Step::CCTPApproveBurn));
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_approve_tx().nonce)
+        );
+    }
+}
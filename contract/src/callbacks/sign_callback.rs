@@ -1,5 +1,13 @@
-use crate::{types::CacheKey, types::PayloadType, Contract, ContractExt};
-use near_sdk::{env, near, require, PromiseError};
+use crate::errors::{ContractError, OrContractError};
+use crate::{
+    ecdsa,
+    types::{
+        ActivityStatus, CacheKey, PayloadType, SignatureDeposit, StepRecord, TransactionParams,
+    },
+    Contract, ContractExt,
+};
+use near_sdk::FunctionError;
+use near_sdk::{env, near, PromiseError};
 use omni_transaction::{
     evm::{types::Signature, EVMTransaction},
     signer::types::SignatureResponse,
@@ -14,68 +22,313 @@ impl Contract {
         nonce: u64,
         tx_type: u8,
         ethereum_tx: EVMTransaction,
+        deposit: SignatureDeposit,
     ) -> Vec<u8> {
-        // Ensure the callback corresponds to the active session.
-        let nonce_from_session = self.get_active_session().nonce;
-        require!(nonce == nonce_from_session, "Nonce mismatch in callback");
+        deposit.refund(call_result.is_ok());
 
         let step =
-            PayloadType::try_from(tx_type).unwrap_or_else(|_| env::panic_str("Unknown tx_type"));
+            PayloadType::try_from(tx_type).unwrap_or_else(|_| ContractError::UnknownTxType.panic());
 
-        // Defense-in-depth: ensure correct order.
-        self.assert_step_is_next(step);
+        // Ensure the callback corresponds to the active session, in the correct order.
+        // @dev a panic here would roll back the refund, so a stale result is only logged
+        if let Err(error) = self
+            .check_callback_session(nonce)
+            .and_then(|_| self.check_step_is_next(step))
+        {
+            env::log_str(&format!("Signature for {:?} discarded: {}", step, error));
+            self.release_evm_nonce(ethereum_tx.chain_id, ethereum_tx.nonce);
+            return vec![];
+        }
 
-        match call_result {
+        let payload_hash = self.hash_payload(&ethereum_tx); // [u8;32]
+        let signature = match call_result {
             Ok(signature_response) => {
-                // decode signature and build signed RLP
-                let affine_point_bytes =
-                    hex::decode(signature_response.big_r.affine_point.clone()).expect("bad affine");
-                require!(affine_point_bytes.len() >= 33, "affine too short");
-
-                let r_bytes = affine_point_bytes[1..33].to_vec();
-                let s_bytes = hex::decode(signature_response.s.scalar.clone()).expect("bad s");
-                require!(s_bytes.len() == 32, "s len != 32");
-                let v = signature_response.recovery_id as u64;
-                let signature_omni = Signature {
-                    v,
-                    r: r_bytes,
-                    s: s_bytes,
-                };
-                let signed_rlp = ethereum_tx.build_with_signature(&signature_omni);
-
-                // payload: tx_type || signed_rlp
-                let mut payload = vec![tx_type];
-                payload.extend(signed_rlp.clone());
-
-                // logs: update ActivityLog with the new signed transaction
-                let mut log = self.logs.get(&nonce).expect("Log not found").clone();
-                log.transactions.retain(|t| t[0] != tx_type);
-                log.transactions.push(payload.clone());
-                self.logs.insert(nonce, log);
-
-                // caches: hash build_for_signing + signed payload
-                let ph = self.hash_payload(&ethereum_tx); // [u8;32]
-                let cache_key = CacheKey { nonce, tx_type };
-
-                self.payload_hashes_by_nonce_and_type
-                    .insert(cache_key.clone(), ph);
-
-                self.signatures_by_nonce_and_type
-                    .insert(cache_key, payload.clone());
-
-                payload
+                ecdsa::verify_signature(&signature_response, &payload_hash, &self.signer)
+                    .ok_or_else(|| ContractError::SignatureMismatch.to_string())
+            }
+            Err(e) => Err(format!("Callback failed: {:?}", e)),
+        };
+
+        match signature {
+            Ok(signature_omni) => {
+                self.cache_step_signature(nonce, step, &ethereum_tx, payload_hash, &signature_omni)
             }
-            Err(e) => {
-                env::log_str(&format!("Callback failed: {:?}", e));
+            Err(reason) => {
+                env::log_str(&reason);
+                self.release_evm_nonce(ethereum_tx.chain_id, ethereum_tx.nonce);
+                self.mark_step_signature_failed(nonce, step);
                 vec![]
             }
         }
     }
 }
 
+impl Contract {
+    pub(crate) fn cache_step_signature(
+        &mut self,
+        nonce: u64,
+        step: PayloadType,
+        ethereum_tx: &EVMTransaction,
+        payload_hash: [u8; 32],
+        signature: &Signature,
+    ) -> Vec<u8> {
+        let tx_type = step as u8;
+        let signed_rlp = ethereum_tx.build_with_signature(signature);
+
+        // payload: tx_type || signed_rlp
+        let mut payload = vec![tx_type];
+        payload.extend(signed_rlp);
+
+        // logs: update ActivityLog with the new signed transaction
+        let log = self
+            .logs
+            .get_mut(&nonce)
+            .or_error(ContractError::LogNotFound);
+        log.transactions.retain(|t| t[0] != tx_type);
+        log.transactions.push(payload.clone());
+        log.steps.retain(|record| record.step != step);
+        log.steps.push(StepRecord {
+            step,
+            signed_at: env::block_timestamp_ms(),
+            chain_id: ethereum_tx.chain_id,
+            evm_nonce: ethereum_tx.nonce,
+            confirmation: None,
+        });
+        log.set_status(ActivityStatus::InProgress, None);
+
+        // caches: hash build_for_signing + signed payload
+        let cache_key = CacheKey { nonce, tx_type };
+
+        self.payload_hashes_by_nonce_and_type
+            .insert(cache_key.clone(), payload_hash);
+
+        self.signatures_by_nonce_and_type
+            .insert(cache_key.clone(), payload.clone());
+
+        self.transactions_by_nonce_and_type
+            .insert(cache_key.clone(), TransactionParams::from(ethereum_tx));
+
+        self.original_fees_by_nonce_and_type.insert(
+            cache_key,
+            (
+                ethereum_tx.max_fee_per_gas,
+                ethereum_tx.max_priority_fee_per_gas,
+            ),
+        );
+
+        payload
+    }
+
+    pub(crate) fn mark_step_signature_failed(&mut self, nonce: u64, step: PayloadType) {
+        self.logs
+            .get_mut(&nonce)
+            .or_error(ContractError::LogNotFound)
+            .set_status(
+                ActivityStatus::Failed,
+                Some(format!("Signature request failed for {:?}", step)),
+            );
+    }
+}
+
 #[cfg(test)]
 mod maintests {
+    use crate::constants::DEFAULT_SIGNATURE_DEPOSIT;
     use crate::test_helpers::*;
     use crate::types::*;
-    use near_sdk::env;
+    use near_sdk::{env, PromiseError};
+
+    fn start_session() -> (crate::Contract, u64) {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let nonce = contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            1_000,
+        );
+
+        set_context(&env::current_account_id().to_string());
+        (contract, nonce)
+    }
+
+    #[test]
+    fn test_sign_callback_records_step() {
+        let (mut contract, nonce) = start_session();
+        let tx_type = Step::AaveWithdraw as u8;
+        let signature = build_tx_signature(&contract, &build_partial_tx());
+
+        let payload = contract.sign_callback(
+            Ok(signature),
+            nonce,
+            tx_type,
+            build_partial_tx(),
+            build_signature_deposit(),
+        );
+
+        assert_eq!(payload[0], tx_type);
+        assert!(contract.has_signature(Step::AaveWithdraw));
+
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.status, ActivityStatus::InProgress);
+        assert_eq!(log.steps.len(), 1);
+        assert_eq!(log.steps[0].step, Step::AaveWithdraw);
+        assert_eq!(log.steps[0].signed_at, env::block_timestamp_ms());
+        assert_eq!(log.steps[0].chain_id, DEFAULT_SOURCE_CHAIN);
+        assert_eq!(log.steps[0].evm_nonce, build_partial_tx().nonce);
+        assert_eq!(log.transactions, vec![payload]);
+        assert_eq!(
+            get_created_transfers(),
+            vec![(
+                WORKER.parse().unwrap(),
+                DEFAULT_ATTACHED_DEPOSIT.saturating_sub(DEFAULT_SIGNATURE_DEPOSIT)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_sign_callback_marks_failure() {
+        let (mut contract, nonce) = start_session();
+        let tx = build_partial_tx();
+        contract
+            .next_evm_nonce_by_chain
+            .insert(tx.chain_id, tx.nonce + 1); // @dev reserved by the failed request
+
+        let payload = contract.sign_callback(
+            Err(PromiseError::Failed),
+            nonce,
+            Step::AaveWithdraw as u8,
+            build_partial_tx(),
+            build_signature_deposit(),
+        );
+
+        assert!(payload.is_empty());
+        assert!(!contract.has_signature(Step::AaveWithdraw));
+        assert_eq!(contract.get_next_nonce(tx.chain_id), Some(tx.nonce));
+
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.status, ActivityStatus::Failed);
+        assert!(log.failure_reason.is_some());
+        assert_eq!(
+            get_created_transfers(),
+            vec![(WORKER.parse().unwrap(), DEFAULT_ATTACHED_DEPOSIT)]
+        );
+    }
+
+    #[test]
+    fn test_sign_callback_rejects_signature_from_another_key() {
+        let (mut contract, nonce) = start_session();
+        let tx = build_partial_tx();
+        contract
+            .next_evm_nonce_by_chain
+            .insert(tx.chain_id, tx.nonce + 1);
+        // @dev a valid signature over another payload recovers to an unrelated address
+        let signature = build_signature(&contract, [7u8; 32]);
+
+        let payload = contract.sign_callback(
+            Ok(signature),
+            nonce,
+            Step::AaveWithdraw as u8,
+            tx.clone(),
+            build_signature_deposit(),
+        );
+
+        assert!(payload.is_empty());
+        assert!(!contract.has_signature(Step::AaveWithdraw));
+        assert_eq!(contract.get_next_nonce(tx.chain_id), Some(tx.nonce));
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Failed
+        );
+    }
+
+    #[test]
+    fn test_sign_callback_rejects_malformed_signature() {
+        let (mut contract, nonce) = start_session();
+        let tx = build_partial_tx();
+        contract
+            .next_evm_nonce_by_chain
+            .insert(tx.chain_id, tx.nonce + 1);
+        let mut signature = build_tx_signature(&contract, &tx);
+        signature.big_r.affine_point = "not hex".to_string();
+
+        let payload = contract.sign_callback(
+            Ok(signature),
+            nonce,
+            Step::AaveWithdraw as u8,
+            tx.clone(),
+            build_signature_deposit(),
+        );
+
+        assert!(payload.is_empty());
+        assert!(!contract.has_signature(Step::AaveWithdraw));
+        assert_eq!(contract.get_next_nonce(tx.chain_id), Some(tx.nonce));
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Failed
+        );
+        assert_eq!(
+            get_created_transfers(),
+            vec![(
+                WORKER.parse().unwrap(),
+                DEFAULT_ATTACHED_DEPOSIT.saturating_sub(DEFAULT_SIGNATURE_DEPOSIT)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_sign_callback_refunds_after_session_was_aborted() {
+        let (mut contract, nonce) = start_session();
+        let tx = build_partial_tx();
+        contract
+            .next_evm_nonce_by_chain
+            .insert(tx.chain_id, tx.nonce + 1);
+        let signature = build_tx_signature(&contract, &tx);
+        contract.active_session = None;
+
+        let payload = contract.sign_callback(
+            Ok(signature),
+            nonce,
+            Step::AaveWithdraw as u8,
+            build_partial_tx(),
+            build_signature_deposit(),
+        );
+
+        assert!(payload.is_empty());
+        assert_eq!(contract.get_next_nonce(tx.chain_id), Some(tx.nonce));
+        assert_eq!(
+            get_created_transfers(),
+            vec![(
+                WORKER.parse().unwrap(),
+                DEFAULT_ATTACHED_DEPOSIT.saturating_sub(DEFAULT_SIGNATURE_DEPOSIT)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_sign_callback_discards_step_that_is_no_longer_next() {
+        let (mut contract, nonce) = start_session();
+        let mut tx = build_partial_tx();
+        tx.nonce += 1;
+        contract
+            .next_evm_nonce_by_chain
+            .insert(tx.chain_id, tx.nonce + 1);
+        let signature = build_tx_signature(&contract, &tx);
+
+        let payload = contract.sign_callback(
+            Ok(signature),
+            nonce,
+            Step::CCTPBurn as u8,
+            tx.clone(),
+            build_signature_deposit(),
+        );
+
+        assert!(payload.is_empty());
+        assert!(!contract.has_signature(Step::CCTPBurn));
+        assert_eq!(contract.get_next_nonce(tx.chain_id), Some(tx.nonce));
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Started
+        );
+    }
 }
@@ -1,5 +1,6 @@
-use crate::{Contract, ContractExt};
-use near_sdk::{env, near, require, PromiseError};
+use crate::errors::ContractError;
+use crate::{ecdsa, types::SignatureDeposit, Contract, ContractExt};
+use near_sdk::{env, near, PromiseError};
 use omni_transaction::signer::types::SignatureResponse;
 
 #[near]
@@ -8,25 +9,30 @@ impl Contract {
     pub fn sign_crosschain_balance_callback(
         &mut self,
         #[callback_result] call_result: Result<SignatureResponse, PromiseError>,
+        payload_hash: [u8; 32],
+        deposit: SignatureDeposit,
     ) -> Vec<u8> {
+        deposit.refund(call_result.is_ok());
+
         match call_result {
             Ok(signature_response) => {
-                // decode signature and build it into [u8;65]
-                let affine_point_bytes =
-                    hex::decode(signature_response.big_r.affine_point.clone()).expect("bad affine");
-                require!(affine_point_bytes.len() >= 33, "affine too short");
-
-                let r_bytes = affine_point_bytes[1..33].to_vec();
-                let s_bytes = hex::decode(signature_response.s.scalar.clone()).expect("bad s");
-                require!(s_bytes.len() == 32, "s len != 32");
-                let v = signature_response.recovery_id as u8;
+                let Some(signature) =
+                    ecdsa::verify_signature(&signature_response, &payload_hash, &self.signer)
+                else {
+                    env::log_str(&ContractError::SignatureMismatch.to_string());
+                    return vec![];
+                };
 
+                // build it into [u8;65]
                 let mut signature_bytes = Vec::with_capacity(65);
-                signature_bytes.extend_from_slice(&r_bytes);
-                signature_bytes.extend_from_slice(&s_bytes);
-                signature_bytes.push(v);
+                signature_bytes.extend_from_slice(&signature.r);
+                signature_bytes.extend_from_slice(&signature.s);
+                signature_bytes.push(signature.v as u8);
 
-                env::log_str(&format!("✅ MPC signature ready: v={}, r,s ok", v));
+                env::log_str(&format!(
+                    "✅ MPC signature ready: v={}, r,s ok",
+                    signature.v
+                ));
 
                 signature_bytes
             }
@@ -41,6 +47,43 @@ impl Contract {
 #[cfg(test)]
 mod maintests {
     use crate::test_helpers::*;
-    use crate::types::*;
     use near_sdk::env;
+
+    const PAYLOAD_HASH: [u8; 32] = [9u8; 32];
+
+    #[test]
+    fn test_sign_crosschain_balance_callback_returns_rsv() {
+        set_context(&env::current_account_id().to_string());
+        let mut contract = init_contract_with_defaults();
+        let signature = build_signature(&contract, PAYLOAD_HASH);
+        let expected = format!(
+            "{}{}{:02x}",
+            &signature.big_r.affine_point[2..],
+            signature.s.scalar,
+            signature.recovery_id
+        );
+
+        let signature_bytes = contract.sign_crosschain_balance_callback(
+            Ok(signature),
+            PAYLOAD_HASH,
+            build_signature_deposit(),
+        );
+
+        assert_eq!(hex::encode(signature_bytes), expected);
+    }
+
+    #[test]
+    fn test_sign_crosschain_balance_callback_rejects_other_payload() {
+        set_context(&env::current_account_id().to_string());
+        let mut contract = init_contract_with_defaults();
+        let signature = build_signature(&contract, [7u8; 32]);
+
+        assert!(contract
+            .sign_crosschain_balance_callback(
+                Ok(signature),
+                PAYLOAD_HASH,
+                build_signature_deposit()
+            )
+            .is_empty());
+    }
 }
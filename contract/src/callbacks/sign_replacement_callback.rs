@@ -0,0 +1,221 @@
+use crate::errors::{ContractError, OrContractError};
+use crate::{
+    ecdsa,
+    types::{CacheKey, PayloadType, SignatureDeposit, TransactionParams},
+    Contract, ContractExt,
+};
+use near_sdk::FunctionError;
+use near_sdk::{env, near, PromiseError};
+use omni_transaction::{evm::EVMTransaction, signer::types::SignatureResponse};
+
+#[near]
+impl Contract {
+    #[private]
+    pub fn sign_replacement_callback(
+        &mut self,
+        #[callback_result] call_result: Result<SignatureResponse, PromiseError>,
+        nonce: u64,
+        tx_type: u8,
+        ethereum_tx: EVMTransaction,
+        deposit: SignatureDeposit,
+    ) -> Vec<u8> {
+        deposit.refund(call_result.is_ok());
+
+        let step =
+            PayloadType::try_from(tx_type).unwrap_or_else(|_| ContractError::UnknownTxType.panic());
+
+        // @dev a panic here would roll back the refund, so a stale result is only logged
+        if let Err(error) = self.check_callback_session(nonce) {
+            env::log_str(&format!("Replacement for {:?} discarded: {}", step, error));
+            return vec![];
+        }
+
+        match call_result {
+            Ok(signature_response) => {
+                let payload_hash = self.hash_payload(&ethereum_tx);
+                let Some(signature_omni) =
+                    ecdsa::verify_signature(&signature_response, &payload_hash, &self.signer)
+                else {
+                    // @dev the original transaction is still valid, nothing to roll back
+                    env::log_str(&format!(
+                        "Replacement for {:?} rejected: {}",
+                        step,
+                        ContractError::SignatureMismatch
+                    ));
+                    return vec![];
+                };
+                let signed_rlp = ethereum_tx.build_with_signature(&signature_omni);
+
+                // payload: tx_type || signed_rlp
+                let mut payload = vec![tx_type];
+                payload.extend(signed_rlp);
+
+                // logs: keep the replaced transaction in the history
+                let log = self
+                    .logs
+                    .get_mut(&nonce)
+                    .or_error(ContractError::LogNotFound);
+                let (superseded, kept): (Vec<_>, Vec<_>) =
+                    log.transactions.drain(..).partition(|t| t[0] == tx_type);
+                log.transactions = kept;
+                log.transactions.push(payload.clone());
+                log.superseded_transactions.extend(superseded);
+                if let Some(record) = log.steps.iter_mut().find(|record| record.step == step) {
+                    record.signed_at = env::block_timestamp_ms();
+                }
+
+                let cache_key = CacheKey { nonce, tx_type };
+
+                self.payload_hashes_by_nonce_and_type
+                    .insert(cache_key.clone(), payload_hash);
+
+                self.signatures_by_nonce_and_type
+                    .insert(cache_key.clone(), payload.clone());
+
+                self.transactions_by_nonce_and_type
+                    .insert(cache_key, TransactionParams::from(&ethereum_tx));
+
+                payload
+            }
+            Err(e) => {
+                // @dev the original transaction is still valid, nothing to roll back
+                env::log_str(&format!("Replacement for {:?} failed: {:?}", step, e));
+                vec![]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::constants::DEFAULT_SIGNATURE_DEPOSIT;
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::env;
+
+    #[test]
+    fn test_sign_replacement_callback_keeps_superseded_transaction() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let nonce = contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+        let tx_type = Step::AaveWithdraw as u8;
+
+        set_context(&env::current_account_id().to_string());
+        let signature = build_tx_signature(&contract, &build_partial_tx());
+        let original = contract.sign_callback(
+            Ok(signature),
+            nonce,
+            tx_type,
+            build_partial_tx(),
+            build_signature_deposit(),
+        );
+
+        let mut bumped = build_partial_tx();
+        bumped.max_fee_per_gas = 150;
+        let signature = build_tx_signature(&contract, &bumped);
+        let replacement = contract.sign_replacement_callback(
+            Ok(signature),
+            nonce,
+            tx_type,
+            bumped.clone(),
+            build_signature_deposit(),
+        );
+
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.transactions, vec![replacement.clone()]);
+        assert_eq!(log.superseded_transactions, vec![original]);
+        assert_eq!(log.steps.len(), 1);
+
+        let key = CacheKey::new(nonce, tx_type);
+        assert_eq!(
+            contract.signatures_by_nonce_and_type.get(&key),
+            Some(&replacement)
+        );
+        assert_eq!(
+            contract
+                .transactions_by_nonce_and_type
+                .get(&key)
+                .unwrap()
+                .max_fee_per_gas,
+            150
+        );
+    }
+
+    #[test]
+    fn test_sign_replacement_callback_keeps_original_on_mismatch() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let nonce = contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+        let tx_type = Step::AaveWithdraw as u8;
+
+        set_context(&env::current_account_id().to_string());
+        let signature = build_tx_signature(&contract, &build_partial_tx());
+        let original = contract.sign_callback(
+            Ok(signature),
+            nonce,
+            tx_type,
+            build_partial_tx(),
+            build_signature_deposit(),
+        );
+
+        let mut bumped = build_partial_tx();
+        bumped.max_fee_per_gas = 150;
+        let replacement = contract.sign_replacement_callback(
+            Ok(build_mock_signature()),
+            nonce,
+            tx_type,
+            bumped,
+            build_signature_deposit(),
+        );
+
+        assert!(replacement.is_empty());
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.transactions, vec![original.clone()]);
+        assert!(log.superseded_transactions.is_empty());
+        assert_eq!(
+            contract
+                .signatures_by_nonce_and_type
+                .get(&CacheKey::new(nonce, tx_type)),
+            Some(&original)
+        );
+    }
+
+    #[test]
+    fn test_sign_replacement_callback_refunds_after_session_was_aborted() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) =
+            start_session_with_signed_steps(Flow::AaveToAave, &[Step::AaveWithdraw]);
+        contract.active_session = None;
+
+        set_context(&env::current_account_id().to_string());
+        let replacement = contract.sign_replacement_callback(
+            Ok(build_tx_signature(&contract, &build_partial_tx())),
+            nonce,
+            Step::AaveWithdraw as u8,
+            build_partial_tx(),
+            build_signature_deposit(),
+        );
+
+        assert!(replacement.is_empty());
+        assert_eq!(
+            get_created_transfers(),
+            vec![(
+                WORKER.parse().unwrap(),
+                DEFAULT_ATTACHED_DEPOSIT.saturating_sub(DEFAULT_SIGNATURE_DEPOSIT)
+            )]
+        );
+    }
+}
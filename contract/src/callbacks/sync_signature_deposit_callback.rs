@@ -0,0 +1,60 @@
+use crate::errors::{ContractError, OrContractError};
+use crate::{events::emit_event, Contract, ContractExt};
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::{near, NearToken, PromiseError};
+
+#[near]
+impl Contract {
+    #[private]
+    pub fn sync_signature_deposit_callback(
+        &mut self,
+        #[callback_result] call_result: Result<U128, PromiseError>,
+    ) -> NearToken {
+        let required = NearToken::from_yoctonear(
+            call_result
+                .or_error(ContractError::SignatureDepositQueryFailed)
+                .0,
+        );
+
+        emit_event(
+            "signature_deposit_synced",
+            json!({ "signature_deposit": required }),
+        );
+        self.signer.signature_deposit = required;
+
+        required
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use near_sdk::json_types::U128;
+    use near_sdk::{env, NearToken, PromiseError};
+
+    #[test]
+    fn test_sync_stores_the_mpc_requirement() {
+        set_context(OWNER);
+        let mut contract = init_contract_with_defaults();
+
+        set_context(&env::current_account_id().to_string());
+        let required = contract.sync_signature_deposit_callback(Ok(U128(1)));
+
+        assert_eq!(required, NearToken::from_yoctonear(1));
+        assert_eq!(
+            contract.get_signer_config().signature_deposit,
+            NearToken::from_yoctonear(1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Signature deposit query failed")]
+    fn fails_if_the_query_failed() {
+        set_context(OWNER);
+        let mut contract = init_contract_with_defaults();
+
+        set_context(&env::current_account_id().to_string());
+        contract.sync_signature_deposit_callback(Err(PromiseError::Failed));
+    }
+}
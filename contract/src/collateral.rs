@@ -1,88 +1,89 @@
+use crate::errors::{ensure, ContractError, OrContractError};
 use dcap_qvl::QuoteCollateralV3;
 use hex::{decode, encode};
-use near_sdk::require;
 use serde_json::Value;
 use sha2::{Digest as _, Sha256, Sha384};
 
 pub fn get_collateral(raw_quote_collateral: String) -> QuoteCollateralV3 {
-    let quote_collateral: serde_json::Value =
-        serde_json::from_str(&raw_quote_collateral).expect("TCB Info should be valid JSON");
-
-    let tcb_info_issuer_chain = quote_collateral["tcb_info_issuer_chain"]
-        .as_str()
-        .unwrap()
-        .to_owned();
-    let tcb_info = quote_collateral["tcb_info"].as_str().unwrap().to_owned();
-    let tcb_info_signature =
-        hex::decode(quote_collateral["tcb_info_signature"].as_str().unwrap()).unwrap();
-    let qe_identity_issuer_chain = quote_collateral["qe_identity_issuer_chain"]
-        .as_str()
-        .unwrap()
-        .to_owned();
-    let qe_identity = quote_collateral["qe_identity"].as_str().unwrap().to_owned();
-    let qe_identity_signature =
-        hex::decode(quote_collateral["qe_identity_signature"].as_str().unwrap()).unwrap();
+    let quote_collateral: serde_json::Value = serde_json::from_str(&raw_quote_collateral)
+        .or_error(ContractError::InvalidCollateral("json"));
 
     QuoteCollateralV3 {
-        tcb_info_issuer_chain,
-        tcb_info,
-        tcb_info_signature,
-        qe_identity_issuer_chain,
-        qe_identity,
-        qe_identity_signature,
+        tcb_info_issuer_chain: str_field(&quote_collateral, "tcb_info_issuer_chain").to_owned(),
+        tcb_info: str_field(&quote_collateral, "tcb_info").to_owned(),
+        tcb_info_signature: hex_field(&quote_collateral, "tcb_info_signature"),
+        qe_identity_issuer_chain: str_field(&quote_collateral, "qe_identity_issuer_chain")
+            .to_owned(),
+        qe_identity: str_field(&quote_collateral, "qe_identity").to_owned(),
+        qe_identity_signature: hex_field(&quote_collateral, "qe_identity_signature"),
     }
 }
 
 pub fn verify_codehash(raw_tcb_info: String, rtmr3: String) -> String {
     let tcb_info: Value =
-        serde_json::from_str(&raw_tcb_info).expect("TCB Info should be valid JSON");
-    let event_log = tcb_info["event_log"].as_array().unwrap();
+        serde_json::from_str(&raw_tcb_info).or_error(ContractError::InvalidCollateral("tcb_info"));
+    let event_log = tcb_info["event_log"]
+        .as_array()
+        .or_error(ContractError::InvalidCollateral("event_log"));
     // get compose hash from events
     let expected_compose_hash = event_log
         .iter()
-        .filter(|e| e["event"].as_str().unwrap() == "compose-hash")
-        .next()
-        .unwrap()["digest"]
-        .as_str()
-        .unwrap();
+        .find(|e| e["event"].as_str() == Some("compose-hash"))
+        .and_then(|e| e["digest"].as_str())
+        .or_error(ContractError::InvalidCollateral("compose-hash event"));
 
     // replay the rtmr3 and compose hash
-    let replayed_rtmr3 = replay_rtmr(event_log.to_owned(), 3);
-    let app_compose = tcb_info["app_compose"].as_str().unwrap();
+    let replayed_rtmr3 = replay_rtmr(event_log, 3);
+    let app_compose = str_field(&tcb_info, "app_compose");
     let replayed_compose_hash: String = replay_app_compose(app_compose);
 
     // compose hash match expected
-    require!(replayed_compose_hash == expected_compose_hash);
+    ensure!(
+        replayed_compose_hash == expected_compose_hash,
+        ContractError::CodehashMismatch
+    );
 
     // event with compose hash matches report rtmr3
-    require!(replayed_rtmr3 == rtmr3);
+    ensure!(replayed_rtmr3 == rtmr3, ContractError::CodehashMismatch);
 
-    let (_, right) = app_compose.split_once("\\n    image:").unwrap();
-    let (left, _) = right.split_once("\\n").unwrap();
-    let (_, codehash) = left.split_once("@sha256:").unwrap();
+    let codehash = app_compose
+        .split_once("\\n    image:")
+        .and_then(|(_, right)| right.split_once("\\n"))
+        .and_then(|(left, _)| left.split_once("@sha256:"))
+        .map(|(_, codehash)| codehash)
+        .or_error(ContractError::InvalidCollateral("app_compose image"));
 
     codehash.to_owned()
 }
 
 // helpers
 
-fn replay_rtmr(event_log: Vec<Value>, imr: u8) -> String {
+fn str_field<'a>(value: &'a Value, field: &'static str) -> &'a str {
+    value[field]
+        .as_str()
+        .or_error(ContractError::InvalidCollateral(field))
+}
+
+fn hex_field(value: &Value, field: &'static str) -> Vec<u8> {
+    decode(str_field(value, field)).or_error(ContractError::InvalidCollateral(field))
+}
+
+fn replay_rtmr(event_log: &[Value], imr: u8) -> String {
     let mut digest = [0u8; 48];
 
     // filter by imr
-    let filtered_events = event_log
-        .iter()
-        .filter(|e| e["imr"].as_u64().unwrap() as u8 == imr);
+    let filtered_events = event_log.iter().filter(|e| {
+        e["imr"]
+            .as_u64()
+            .or_error(ContractError::InvalidCollateral("imr")) as u8
+            == imr
+    });
 
     // hash all digests together
     for event in filtered_events {
         let mut hasher = Sha384::new();
         hasher.update(digest);
-        hasher.update(
-            decode(event["digest"].as_str().unwrap())
-                .unwrap()
-                .as_slice(),
-        );
+        hasher.update(hex_field(event, "digest").as_slice());
         digest = hasher.finalize().into();
     }
 
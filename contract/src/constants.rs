@@ -2,7 +2,20 @@ use near_sdk::{Gas, NearToken};
 
 pub const MPC_CONTRACT_ACCOUNT_ID: &str = "v1.signer";
 pub const MPC_CONTRACT_ACCOUNT_ID_TESTNET: &str = "v1.signer-prod.testnet";
-pub const PATH: &str = "ethereum-1";
-pub const KEY_VERSION: u32 = 0;
+pub const MPC_ROOT_PUBLIC_KEY: &str = "secp256k1:3tFRbMqmoa6AAALMrEFAYCEoHcqKxeW38YptwowBVBtXK1vo36HDbUWuR6EZmoK4JcH6HDkNMGGqP1ouV7VZUWya";
+pub const MPC_ROOT_PUBLIC_KEY_TESTNET: &str = "secp256k1:4NfTiv3UsGahebgTaHyD9vF8KYKMBnfd6kh94mK6xv8fGBiJB8TBtFMP5WWXz6B89Ac1fbpzPwAvoyQebemHFwx3";
+pub const DEFAULT_PATH: &str = "ethereum-1";
+pub const DEFAULT_KEY_VERSION: u32 = 0;
 pub const CALLBACK_GAS: Gas = Gas::from_tgas(50);
-pub const ATTACHED_DEPOSIT: NearToken = NearToken::from_yoctonear(500000000000000000000000);
+pub const MIGRATE_GAS: Gas = Gas::from_tgas(100);
+pub const SIGNATURE_DEPOSIT_QUERY_GAS: Gas = Gas::from_tgas(10);
+pub const DEFAULT_SIGNATURE_DEPOSIT: NearToken =
+    NearToken::from_yoctonear(500000000000000000000000);
+pub const STATE_VERSION: u32 = 1;
+pub const DEFAULT_SESSION_TIMEOUT_MS: u64 = 60 * 60 * 1000; // 1 hour
+pub const MIN_FEE_BUMP_BPS: u128 = 11_000; // +10%, the minimum most mempools accept for a replacement
+pub const OWNER_PROPOSAL_TTL_MS: u64 = 7 * 24 * 60 * 60 * 1000; // 7 days
+pub const DEFAULT_FEE_BUMP_CEILING_BPS: u128 = 30_000; // 3x the replaced fees
+pub const DEFAULT_CONFIG_TIMELOCK_MS: u64 = 48 * 60 * 60 * 1000; // 48 hours
+pub const MAX_CONFIG_TIMELOCK_MS: u64 = 30 * 24 * 60 * 60 * 1000; // 30 days
+pub const MAX_BATCH_SIZE: usize = 4; // each sign call is given CALLBACK_GAS, 4 of them and the join callback fit in 300 Tgas
@@ -0,0 +1,114 @@
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::elliptic_curve::{CurveArithmetic, PrimeField};
+use k256::{AffinePoint, EncodedPoint, Scalar, Secp256k1};
+use near_sdk::{AccountId, CurveType, PublicKey};
+use sha3::{Digest, Keccak256, Sha3_256};
+
+use crate::errors::{ensure, ContractError, OrContractError};
+use crate::types::EvmAddress;
+
+// @dev same derivation as the MPC contract, ported from tests/utils/mpc/addresses.rs
+const EPSILON_DERIVATION_PREFIX: &str = "near-mpc-recovery v0.1.0 epsilon derivation:";
+
+pub fn derive_epsilon(predecessor_id: &AccountId, path: &str) -> Scalar {
+    let derivation_path = format!("{EPSILON_DERIVATION_PREFIX}{},{}", predecessor_id, path);
+    let hash: [u8; 32] = Sha3_256::digest(derivation_path.as_bytes()).into();
+
+    // @dev a hash outside the field takes ~2^127 attempts to find
+    Scalar::from_repr(hash.into())
+        .into_option()
+        .or_error(ContractError::KeyDerivationFailed)
+}
+
+pub fn derive_key(public_key: AffinePoint, epsilon: Scalar) -> AffinePoint {
+    (<Secp256k1 as CurveArithmetic>::ProjectivePoint::GENERATOR * epsilon + public_key).to_affine()
+}
+
+pub fn public_key_to_evm_address(public_key: AffinePoint) -> EvmAddress {
+    let encoded_point = public_key.to_encoded_point(false);
+    let hash = Keccak256::digest(&encoded_point.as_bytes()[1..]); // @dev without the 0x04 prefix
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    EvmAddress(address)
+}
+
+pub fn root_public_key_to_point(root_public_key: &PublicKey) -> AffinePoint {
+    ensure!(
+        root_public_key.curve_type() == CurveType::SECP256K1,
+        ContractError::InvalidRootKey
+    );
+
+    // @dev the key bytes are prefixed by their curve type, the point itself is x || y
+    let mut uncompressed = vec![0x04];
+    uncompressed.extend_from_slice(&root_public_key.as_bytes()[1..]);
+
+    let encoded_point =
+        EncodedPoint::from_bytes(&uncompressed).or_error(ContractError::InvalidRootKey);
+    AffinePoint::from_encoded_point(&encoded_point)
+        .into_option()
+        .or_error(ContractError::InvalidRootKey)
+}
+
+pub fn derive_public_key(
+    root_public_key: &PublicKey,
+    predecessor_id: &AccountId,
+    path: &str,
+) -> AffinePoint {
+    let epsilon = derive_epsilon(predecessor_id, path);
+    derive_key(root_public_key_to_point(root_public_key), epsilon)
+}
+
+pub fn derive_evm_address(
+    root_public_key: &PublicKey,
+    predecessor_id: &AccountId,
+    path: &str,
+) -> EvmAddress {
+    public_key_to_evm_address(derive_public_key(root_public_key, predecessor_id, path))
+}
+
+#[cfg(test)]
+mod maintests {
+    use super::*;
+
+    const ROOT_PUBLIC_KEY: &str = "secp256k1:4NfTiv3UsGahebgTaHyD9vF8KYKMBnfd6kh94mK6xv8fGBiJB8TBtFMP5WWXz6B89Ac1fbpzPwAvoyQebemHFwx3";
+
+    #[test]
+    fn test_derive_key() {
+        let epsilon = derive_epsilon(&"omnitester.testnet".parse().unwrap(), "bitcoin-1");
+        let root = root_public_key_to_point(&ROOT_PUBLIC_KEY.parse().unwrap());
+
+        let derived_public_key = derive_key(root, epsilon);
+
+        assert_eq!(
+            hex::encode(derived_public_key.to_encoded_point(false).as_bytes()),
+            "0471f75dc56b971fbe52dd3e80d2f8532eb8905157556df39cb7338a67c80412640c869f717217ba5b916db6d7dc7d6a84220f8251e626adad62cac9c7d6f8e032"
+        );
+    }
+
+    #[test]
+    fn test_derive_evm_address() {
+        let address = derive_evm_address(
+            &ROOT_PUBLIC_KEY.parse().unwrap(),
+            &"omnitester.testnet".parse().unwrap(),
+            "ethereum-1",
+        );
+
+        assert_eq!(
+            address,
+            "0xd8d25820c9b9e2aa9cce55504355e500efcce715"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Root key must be a secp256k1 key")]
+    fn fails_with_ed25519_root_key() {
+        let root: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap();
+
+        root_public_key_to_point(&root);
+    }
+}
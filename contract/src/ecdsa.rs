@@ -1,27 +1,287 @@
+use crate::errors::{ensure, ContractError, OrContractError};
+use crate::external::{mpc_contract, mpc_contract_v2, PayloadV2, SignRequest, SignRequestV2};
 use crate::{
-    constants::{
-        ATTACHED_DEPOSIT, CALLBACK_GAS, MPC_CONTRACT_ACCOUNT_ID, MPC_CONTRACT_ACCOUNT_ID_TESTNET,
-    },
-    *,
+    constants::CALLBACK_GAS,
+    derivation,
+    types::{SignRequestFormat, SignatureDomain, SignerConfig},
 };
-use external::{mpc_contract, SignRequest};
-use near_sdk::Promise;
-
-pub fn get_sig(payload: [u8; 32], path: String, key_version: u32) -> Promise {
-    let request = SignRequest {
-        payload,
-        path,
-        key_version,
-    };
+use k256::elliptic_curve::scalar::IsHigh;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+use k256::Scalar;
+use near_sdk::{env, Promise};
+use omni_transaction::{evm::types::Signature, signer::types::SignatureResponse};
+
+pub fn get_sig(payload: [u8; 32], signer: &SignerConfig) -> Promise {
+    request_signature(SignatureDomain::Ecdsa, payload.to_vec(), signer)
+}
+
+// @dev the legacy interface only signs 32 byte ECDSA payloads. The deposit is collected from the caller
+// beforehand, see SignatureDeposit
+pub fn request_signature(
+    domain: SignatureDomain,
+    payload: Vec<u8>,
+    signer: &SignerConfig,
+) -> Promise {
+    if signer.request_format == SignRequestFormat::Legacy {
+        ensure!(
+            domain == SignatureDomain::Ecdsa,
+            ContractError::UnsupportedSignatureDomain
+        );
+        let request = SignRequest {
+            payload: payload.try_into().or_error(ContractError::InvalidPayload),
+            path: signer.path.clone(),
+            key_version: signer.key_version,
+        };
+
+        return mpc_contract::ext(signer.mpc_contract_id.clone())
+            .with_static_gas(CALLBACK_GAS)
+            .with_attached_deposit(signer.signature_deposit)
+            .sign(request);
+    }
 
-    let mpc_contract_id = if env::current_account_id().as_str().contains("testnet") {
-        MPC_CONTRACT_ACCOUNT_ID_TESTNET
-    } else {
-        MPC_CONTRACT_ACCOUNT_ID
+    let payload_v2 = match domain {
+        SignatureDomain::Ecdsa => PayloadV2::Ecdsa(hex::encode(payload)),
+        SignatureDomain::Eddsa => PayloadV2::Eddsa(hex::encode(payload)),
+    };
+    let request = SignRequestV2 {
+        payload_v2,
+        path: signer.path.clone(),
+        domain_id: signer
+            .request_format
+            .domain_id(domain)
+            .or_error(ContractError::UnsupportedSignatureDomain),
     };
 
-    mpc_contract::ext(mpc_contract_id.parse().unwrap())
+    mpc_contract_v2::ext(signer.mpc_contract_id.clone())
         .with_static_gas(CALLBACK_GAS)
-        .with_attached_deposit(ATTACHED_DEPOSIT)
+        .with_attached_deposit(signer.signature_deposit)
         .sign(request)
 }
+
+// @dev EVM chains only accept s in the lower half of the curve order, a high s is mirrored and
+// the recovery id flipped so the signature still recovers to the same key. None for a malformed
+// response, it reaches the callbacks and must not panic there
+pub fn decode_signature(signature_response: &SignatureResponse) -> Option<Signature> {
+    let affine_point_bytes = hex::decode(&signature_response.big_r.affine_point).ok()?;
+    if affine_point_bytes.len() < 33 {
+        return None;
+    }
+
+    let s_bytes: [u8; 32] = hex::decode(&signature_response.s.scalar)
+        .ok()?
+        .try_into()
+        .ok()?;
+    let mut s = Scalar::from_repr(s_bytes.into()).into_option()?;
+    // @dev ecrecover aborts on a recovery id above 3, and only 0 and 1 are valid for EVM
+    let mut recovery_id = signature_response.recovery_id;
+    if recovery_id > 1 {
+        return None;
+    }
+    if bool::from(s.is_high()) {
+        s = -s;
+        recovery_id ^= 1;
+    }
+
+    Some(Signature {
+        v: recovery_id as u64,
+        r: affine_point_bytes[1..33].to_vec(),
+        s: s.to_bytes().to_vec(),
+    })
+}
+
+// @dev None when the signature does not recover to the agent key, EVM chains would either reject
+// it or attribute the transaction to another address
+pub fn verify_signature(
+    signature_response: &SignatureResponse,
+    payload_hash: &[u8; 32],
+    signer: &SignerConfig,
+) -> Option<Signature> {
+    let signature = decode_signature(signature_response)?;
+    let agent_public_key = derivation::derive_public_key(
+        &signer.root_public_key,
+        &env::current_account_id(),
+        &signer.path,
+    );
+
+    let mut rs = signature.r.clone();
+    rs.extend_from_slice(&signature.s);
+    let recovered = env::ecrecover(payload_hash, &rs, signature.v as u8, true)?;
+
+    (recovered[..] == agent_public_key.to_encoded_point(false).as_bytes()[1..]).then_some(signature)
+}
+
+#[cfg(test)]
+mod maintests {
+    use super::*;
+    use crate::test_helpers::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::serde_json::{json, Value};
+    use near_sdk::test_utils::get_created_receipts;
+    use near_sdk::{env, NearToken};
+    use omni_transaction::signer::types::{SerializableAffinePoint, SerializableScalar};
+
+    fn v2_signer() -> SignerConfig {
+        SignerConfig {
+            request_format: SignRequestFormat::V2 {
+                ecdsa_domain_id: 0,
+                eddsa_domain_id: 1,
+            },
+            ..SignerConfig::default_for(&env::current_account_id())
+        }
+    }
+
+    fn sign_request_args() -> Value {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .find_map(|action| match action {
+                MockAction::FunctionCallWeight {
+                    method_name, args, ..
+                } if method_name == b"sign" => {
+                    Some(near_sdk::serde_json::from_slice(&args).unwrap())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_legacy_request() {
+        set_context(OWNER);
+        let signer = SignerConfig::default_for(&env::current_account_id());
+
+        get_sig([1u8; 32], &signer);
+
+        let request = &sign_request_args()["request"];
+        assert_eq!(request["payload"], json!([1u8; 32]));
+        assert_eq!(request["path"], json!(signer.path));
+        assert_eq!(request["key_version"], json!(signer.key_version));
+    }
+
+    #[test]
+    fn test_request_attaches_signature_deposit() {
+        set_context(OWNER);
+        let signer = SignerConfig {
+            signature_deposit: NearToken::from_millinear(1),
+            ..SignerConfig::default_for(&env::current_account_id())
+        };
+
+        get_sig([1u8; 32], &signer);
+
+        let attached = get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .find_map(|action| match action {
+                MockAction::FunctionCallWeight {
+                    method_name,
+                    attached_deposit,
+                    ..
+                } if method_name == b"sign" => Some(attached_deposit),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(attached, signer.signature_deposit);
+    }
+
+    #[test]
+    fn test_v2_request() {
+        set_context(OWNER);
+
+        get_sig([1u8; 32], &v2_signer());
+
+        assert_eq!(
+            sign_request_args()["request"],
+            json!({
+                "payload_v2": { "Ecdsa": hex::encode([1u8; 32]) },
+                "path": v2_signer().path,
+                "domain_id": 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_v2_eddsa_request() {
+        set_context(OWNER);
+
+        request_signature(SignatureDomain::Eddsa, vec![0xab; 48], &v2_signer());
+
+        let request = &sign_request_args()["request"];
+        assert_eq!(
+            request["payload_v2"],
+            json!({ "Eddsa": hex::encode([0xab; 48]) })
+        );
+        assert_eq!(request["domain_id"], json!(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Signer request format does not support this domain")]
+    fn fails_to_request_eddsa_with_legacy_format() {
+        set_context(OWNER);
+        let signer = SignerConfig::default_for(&env::current_account_id());
+
+        request_signature(SignatureDomain::Eddsa, vec![0xab; 32], &signer);
+    }
+
+    #[test]
+    fn test_decode_signature_normalizes_high_s() {
+        set_context(OWNER);
+        let contract = init_contract_with_defaults();
+        let payload_hash = [3u8; 32];
+        let low = build_signature(&contract, payload_hash);
+        let low_s: [u8; 32] = hex::decode(&low.s.scalar).unwrap().try_into().unwrap();
+        let high = SignatureResponse {
+            big_r: SerializableAffinePoint {
+                affine_point: low.big_r.affine_point.clone(),
+            },
+            s: SerializableScalar {
+                scalar: hex::encode((-Scalar::from_repr(low_s.into()).unwrap()).to_bytes()),
+            },
+            recovery_id: low.recovery_id ^ 1,
+        };
+
+        let signature = decode_signature(&high).unwrap();
+
+        assert_eq!(signature.s, low_s.to_vec());
+        assert_eq!(signature.v, low.recovery_id as u64);
+        assert!(verify_signature(&high, &payload_hash, &contract.signer).is_some());
+    }
+
+    #[test]
+    fn test_decode_signature_rejects_malformed_responses() {
+        set_context(OWNER);
+        let contract = init_contract_with_defaults();
+        let valid = build_signature(&contract, [3u8; 32]);
+        let with = |affine_point: &str, scalar: &str, recovery_id: u8| SignatureResponse {
+            big_r: SerializableAffinePoint {
+                affine_point: affine_point.to_string(),
+            },
+            s: SerializableScalar {
+                scalar: scalar.to_string(),
+            },
+            recovery_id,
+        };
+        let r = valid.big_r.affine_point.as_str();
+        let s = valid.s.scalar.as_str();
+
+        assert!(decode_signature(&with("zz", s, 0)).is_none());
+        assert!(decode_signature(&with("02ab", s, 0)).is_none());
+        assert!(decode_signature(&with(r, "zz", 0)).is_none());
+        assert!(decode_signature(&with(r, "abcd", 0)).is_none());
+        assert!(decode_signature(&with(r, &"ff".repeat(32), 0)).is_none());
+        assert!(decode_signature(&with(r, s, 2)).is_none());
+        assert!(decode_signature(&with(r, s, 4)).is_none());
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_another_agent_key() {
+        set_context(OWNER);
+        let contract = init_contract_with_defaults();
+        let payload_hash = [3u8; 32];
+        let signer = SignerConfig::default_for(&env::current_account_id());
+
+        let signature = build_signature(&contract, payload_hash);
+
+        assert!(verify_signature(&signature, &payload_hash, &signer).is_none());
+    }
+}
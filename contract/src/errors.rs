@@ -0,0 +1,384 @@
+use near_sdk::FunctionError;
+use std::fmt;
+
+use crate::constants::{MAX_BATCH_SIZE, MAX_CONFIG_TIMELOCK_MS};
+use crate::types::{ChainId, Flow, Role};
+
+// @dev codes are stable, never reuse or renumber them. Panics read "E<code>: <message>"
+// 1xx access, 2xx session, 3xx transaction validation, 4xx chains and config,
+// 5xx pause, 6xx ownership and upgrades, 7xx worker registration
+#[derive(Debug, Clone, PartialEq, FunctionError)]
+pub enum ContractError {
+    Unauthorized,
+    MissingRole(Role),
+    WorkerNotRegistered,
+    WorkerCodehashNotApproved,
+    SessionBoundToAnotherWorker,
+    CodehashNotApproved,
+    OnlyProposedOwner,
+
+    NoActiveSession,
+    AnotherActionInProgress,
+    SessionExpired,
+    WrongStep,
+    FlowAlreadyFinished,
+    FlowHasUnsignedSteps,
+    NoSkippedSteps,
+    PreviousStepNotConfirmed,
+    StepNotSigned,
+    StepAlreadyConfirmed,
+    SessionAlreadyUnwinding,
+    NothingToUnwind,
+    FundsAlreadyBridged,
+    StepNotInFlow,
+    InvalidFlowStep,
+    LogNotFound,
+    CallbackNonceMismatch,
+    UnknownTxType,
+    MissingCachedSignature,
+    SameSourceAndDestination,
+    FlowNotValidForChains(Flow),
+    FlowHasUnconfirmedSteps,
+
+    AmountDoesNotMatchSession,
+    AmountOutOfBounds,
+    MaxFeeTooHigh,
+    ChainIdMismatch,
+    NonZeroValue,
+    UnexpectedAccessList,
+    NonceAlreadyUsed,
+    NonceGap,
+    ReplacedNonceNeverUsed,
+    ReplacementNonceMismatch,
+    FeeBumpTooLow,
+    FeeBumpAboveCeiling,
+    InvalidTxHash,
+    InvalidAddress(&'static str),
+    InvalidPayload,
+    AaveOnSourceChain,
+    InvalidSessionTimeout,
+    InvalidFeeBumpCeiling,
+    InvalidRootKey,
+    KeyDerivationFailed,
+    UnsupportedSignatureDomain,
+    SignatureMismatch,
+    InsufficientSignatureDeposit,
+    EmptyBatch,
+    BatchTooLarge,
+    NonceOverflow,
+    SignatureDepositQueryFailed,
+
+    ChainNotSupported,
+    ChainNotConfigured,
+    ChainAlreadySupported,
+    ChainDisabled,
+    ChainNotDisabled,
+    ChainUsedByActiveSession,
+    CannotRemoveSourceChain,
+    ConfigChangeNotFound,
+    ConfigChangeTimelocked,
+    UsdcAssetMismatch,
+    SourceChainNotConfigured,
+    ConfigTimelockTooLong,
+    ConfigTimelockOverflow,
+
+    ContractPaused,
+    ChainPaused(ChainId),
+    FlowPaused(Flow),
+
+    AlreadyOwner,
+    NoPendingOwnerProposal,
+    OwnerProposalTimelocked,
+    OwnerProposalExpired,
+    MissingContractCode,
+    NoStateToMigrate,
+    CannotDowngradeState,
+    UnknownStateLayout,
+    MigrationWithActiveSession,
+
+    QuoteNotVerified,
+    InvalidCollateral(&'static str),
+    CodehashMismatch,
+    InvalidQuote,
+}
+
+// @dev the same call can succeed later without changing its arguments
+const RETRYABLE_ERROR_CODES: [u16; 8] = [201, 207, 208, 408, 500, 501, 502, 602];
+
+impl ContractError {
+    pub fn code(&self) -> u16 {
+        use ContractError::*;
+        match self {
+            Unauthorized => 100,
+            MissingRole(_) => 101,
+            WorkerNotRegistered => 102,
+            WorkerCodehashNotApproved => 103,
+            SessionBoundToAnotherWorker => 104,
+            CodehashNotApproved => 105,
+            OnlyProposedOwner => 106,
+
+            NoActiveSession => 200,
+            AnotherActionInProgress => 201,
+            SessionExpired => 202,
+            WrongStep => 203,
+            FlowAlreadyFinished => 204,
+            FlowHasUnsignedSteps => 205,
+            NoSkippedSteps => 206,
+            PreviousStepNotConfirmed => 207,
+            StepNotSigned => 208,
+            StepAlreadyConfirmed => 209,
+            SessionAlreadyUnwinding => 210,
+            NothingToUnwind => 211,
+            FundsAlreadyBridged => 212,
+            StepNotInFlow => 213,
+            InvalidFlowStep => 214,
+            LogNotFound => 215,
+            CallbackNonceMismatch => 216,
+            UnknownTxType => 217,
+            MissingCachedSignature => 218,
+            SameSourceAndDestination => 219,
+            FlowNotValidForChains(_) => 220,
+            FlowHasUnconfirmedSteps => 221,
+
+            AmountDoesNotMatchSession => 300,
+            AmountOutOfBounds => 301,
+            MaxFeeTooHigh => 302,
+            ChainIdMismatch => 303,
+            NonZeroValue => 304,
+            UnexpectedAccessList => 305,
+            NonceAlreadyUsed => 306,
+            NonceGap => 307,
+            ReplacedNonceNeverUsed => 308,
+            ReplacementNonceMismatch => 309,
+            FeeBumpTooLow => 310,
+            FeeBumpAboveCeiling => 311,
+            InvalidTxHash => 312,
+            InvalidAddress(_) => 313,
+            InvalidPayload => 315,
+            AaveOnSourceChain => 316,
+            InvalidSessionTimeout => 317,
+            InvalidFeeBumpCeiling => 318,
+            InvalidRootKey => 319,
+            KeyDerivationFailed => 320,
+            UnsupportedSignatureDomain => 321,
+            SignatureMismatch => 322,
+            InsufficientSignatureDeposit => 323,
+            EmptyBatch => 324,
+            BatchTooLarge => 325,
+            NonceOverflow => 326,
+            SignatureDepositQueryFailed => 327,
+
+            ChainNotSupported => 400,
+            ChainNotConfigured => 401,
+            ChainAlreadySupported => 402,
+            ChainDisabled => 403,
+            ChainNotDisabled => 404,
+            ChainUsedByActiveSession => 405,
+            CannotRemoveSourceChain => 406,
+            ConfigChangeNotFound => 407,
+            ConfigChangeTimelocked => 408,
+            UsdcAssetMismatch => 409,
+            SourceChainNotConfigured => 410,
+            ConfigTimelockTooLong => 411,
+            ConfigTimelockOverflow => 412,
+
+            ContractPaused => 500,
+            ChainPaused(_) => 501,
+            FlowPaused(_) => 502,
+
+            AlreadyOwner => 600,
+            NoPendingOwnerProposal => 601,
+            OwnerProposalTimelocked => 602,
+            OwnerProposalExpired => 603,
+            MissingContractCode => 604,
+            NoStateToMigrate => 605,
+            CannotDowngradeState => 606,
+            UnknownStateLayout => 607,
+            MigrationWithActiveSession => 608,
+
+            QuoteNotVerified => 700,
+            InvalidCollateral(_) => 701,
+            CodehashMismatch => 702,
+            InvalidQuote => 703,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        is_retryable_code(self.code())
+    }
+
+    fn message(&self) -> String {
+        use ContractError::*;
+        match self {
+            Unauthorized => "Unauthorized".into(),
+            MissingRole(role) => format!("Missing role {:?}", role),
+            WorkerNotRegistered => "Worker not registered".into(),
+            WorkerCodehashNotApproved => "Worker codehash not approved".into(),
+            SessionBoundToAnotherWorker => "Session is bound to another worker".into(),
+            CodehashNotApproved => "Codehash not approved".into(),
+            OnlyProposedOwner => "Only the proposed owner can accept".into(),
+
+            NoActiveSession => "No active session".into(),
+            AnotherActionInProgress => "Another action in progress".into(),
+            SessionExpired => "Session expired".into(),
+            WrongStep => "Wrong step for current position".into(),
+            FlowAlreadyFinished => "Flow already finished".into(),
+            FlowHasUnsignedSteps => "Flow has unsigned steps".into(),
+            NoSkippedSteps => "No skipped steps, use complete_rebalance".into(),
+            PreviousStepNotConfirmed => "Previous step not confirmed".into(),
+            StepNotSigned => "Step not signed yet".into(),
+            StepAlreadyConfirmed => "Step already confirmed".into(),
+            SessionAlreadyUnwinding => "Session is already unwinding".into(),
+            NothingToUnwind => "Nothing to unwind, abort the session instead".into(),
+            FundsAlreadyBridged => "Funds already bridged, resume the session instead".into(),
+            StepNotInFlow => "Step not in current flow".into(),
+            InvalidFlowStep => "Invalid (flow, step) combination for chain selection".into(),
+            LogNotFound => "Log not found".into(),
+            CallbackNonceMismatch => "Nonce mismatch in callback".into(),
+            UnknownTxType => "Unknown tx_type".into(),
+            MissingCachedSignature => "Signature must be present if payload hash matches".into(),
+            SameSourceAndDestination => "Source and destination chains must differ".into(),
+            FlowNotValidForChains(flow) => format!(
+                "Flow {:?} does not match the source and destination chains",
+                flow
+            ),
+            FlowHasUnconfirmedSteps => "Flow has unconfirmed steps".into(),
+
+            AmountDoesNotMatchSession => "Amount does not match session".into(),
+            AmountOutOfBounds => "Amount out of bounds for session".into(),
+            MaxFeeTooHigh => "Max fee must be lower than the session amount".into(),
+            ChainIdMismatch => "Transaction chain_id does not match step chain".into(),
+            NonZeroValue => "Transaction value must be zero".into(),
+            UnexpectedAccessList => "Unexpected access list".into(),
+            NonceAlreadyUsed => "Nonce already used".into(),
+            NonceGap => "Nonce gap".into(),
+            ReplacedNonceNeverUsed => "Replaced nonce was never used".into(),
+            ReplacementNonceMismatch => {
+                "Replacement must reuse the original chain_id and nonce".into()
+            }
+            FeeBumpTooLow => "Fee bump too low".into(),
+            FeeBumpAboveCeiling => "Fee bump above ceiling".into(),
+            InvalidTxHash => "Invalid tx hash".into(),
+            InvalidAddress(name) => format!("Invalid {}", name),
+            InvalidPayload => "Payload must be 32 bytes long".into(),
+            AaveOnSourceChain => "Aave on the source chain is reached through the vault".into(),
+            InvalidSessionTimeout => "Timeout must be greater than zero".into(),
+            InvalidFeeBumpCeiling => "Ceiling must allow the minimum fee bump".into(),
+            InvalidRootKey => "Root key must be a secp256k1 key".into(),
+            KeyDerivationFailed => "Key derivation failed".into(),
+            UnsupportedSignatureDomain => {
+                "Signer request format does not support this domain".into()
+            }
+            SignatureMismatch => "Signature does not recover to the agent address".into(),
+            InsufficientSignatureDeposit => {
+                "Attached deposit does not cover the signature deposit".into()
+            }
+            EmptyBatch => "Batch is empty".into(),
+            BatchTooLarge => format!("Batch exceeds {} requests", MAX_BATCH_SIZE),
+            NonceOverflow => "Nonce overflow".into(),
+            SignatureDepositQueryFailed => "Signature deposit query failed".into(),
+
+            ChainNotSupported => "Chain not supported".into(),
+            ChainNotConfigured => "Chain not configured".into(),
+            ChainAlreadySupported => "Chain already supported".into(),
+            ChainDisabled => "Chain disabled".into(),
+            ChainNotDisabled => "Chain not disabled".into(),
+            ChainUsedByActiveSession => "Chain used by the active session".into(),
+            CannotRemoveSourceChain => "Cannot remove the source chain".into(),
+            ConfigChangeNotFound => "Config change not found".into(),
+            ConfigChangeTimelocked => "Config change is timelocked".into(),
+            UsdcAssetMismatch => "CCTP usdc_address must match aave.asset".into(),
+            SourceChainNotConfigured => "Source chain not configured".into(),
+            ConfigTimelockTooLong => {
+                format!("Config timelock exceeds {} ms", MAX_CONFIG_TIMELOCK_MS)
+            }
+            ConfigTimelockOverflow => "Config change executable_at overflows".into(),
+
+            ContractPaused => "Contract is paused".into(),
+            ChainPaused(chain_id) => format!("Chain {} is paused", chain_id),
+            FlowPaused(flow) => format!("Flow {:?} is paused", flow),
+
+            AlreadyOwner => "Account is already the owner".into(),
+            NoPendingOwnerProposal => "No pending owner proposal".into(),
+            OwnerProposalTimelocked => "Owner proposal is timelocked".into(),
+            OwnerProposalExpired => "Owner proposal expired".into(),
+            MissingContractCode => "Missing contract code".into(),
+            NoStateToMigrate => "No state to migrate".into(),
+            CannotDowngradeState => "Cannot downgrade contract state".into(),
+            UnknownStateLayout => "Unknown contract state layout".into(),
+            MigrationWithActiveSession => "Complete the active session before migrating".into(),
+
+            QuoteNotVerified => "Report is not verified".into(),
+            InvalidCollateral(field) => format!("Invalid collateral {}", field),
+            CodehashMismatch => "Replayed measurements do not match the report".into(),
+            InvalidQuote => "Invalid quote".into(),
+        }
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{}: {}", self.code(), self.message())
+    }
+}
+
+pub fn is_retryable_code(code: u16) -> bool {
+    RETRYABLE_ERROR_CODES.contains(&code)
+}
+
+pub(crate) trait OrContractError<T> {
+    fn or_error(self, error: ContractError) -> T;
+}
+
+impl<T> OrContractError<T> for Option<T> {
+    fn or_error(self, error: ContractError) -> T {
+        self.unwrap_or_else(|| error.panic())
+    }
+}
+
+impl<T, E> OrContractError<T> for Result<T, E> {
+    fn or_error(self, error: ContractError) -> T {
+        self.unwrap_or_else(|_| error.panic())
+    }
+}
+
+macro_rules! ensure {
+    ($cond:expr, $error:expr $(,)?) => {
+        if !$cond {
+            near_sdk::FunctionError::panic(&$error)
+        }
+    };
+}
+
+pub(crate) use ensure;
+
+#[cfg(test)]
+mod maintests {
+    use super::*;
+
+    #[test]
+    fn test_display_includes_code() {
+        assert_eq!(
+            ContractError::WrongStep.to_string(),
+            "E203: Wrong step for current position"
+        );
+        assert_eq!(
+            ContractError::ChainPaused(2).to_string(),
+            "E501: Chain 2 is paused"
+        );
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(ContractError::ContractPaused.is_retryable());
+        assert!(ContractError::AnotherActionInProgress.is_retryable());
+        assert!(!ContractError::WrongStep.is_retryable());
+        assert!(!ContractError::InvalidAddress("lending pool").is_retryable());
+    }
+
+    #[test]
+    #[should_panic(expected = "E305: Unexpected access list")]
+    fn test_ensure_panics_with_code() {
+        ensure!(false, ContractError::UnexpectedAccessList);
+    }
+}
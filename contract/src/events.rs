@@ -0,0 +1,16 @@
+use near_sdk::env;
+use near_sdk::serde_json::{json, Value};
+
+pub const EVENT_STANDARD: &str = "rebalancer";
+pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+// @dev NEP-297 event log
+pub(crate) fn emit_event(event: &str, data: Value) {
+    let log = json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_STANDARD_VERSION,
+        "event": event,
+        "data": [data],
+    });
+    env::log_str(&format!("EVENT_JSON:{}", log));
+}
@@ -1,6 +1,9 @@
-use near_sdk::{ext_contract, serde::Serialize};
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, serde::Serialize, NearToken};
 use omni_transaction::evm::EVMTransaction;
 
+use crate::types::{BatchEntry, SignatureDeposit};
+
 #[derive(Debug, Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct SignRequest {
@@ -9,16 +12,62 @@ pub struct SignRequest {
     pub key_version: u32,
 }
 
+// @dev payloads are hex encoded, 32 bytes for Ecdsa and the raw message for Eddsa
+#[derive(Debug, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PayloadV2 {
+    Ecdsa(String),
+    Eddsa(String),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignRequestV2 {
+    pub payload_v2: PayloadV2,
+    pub path: String,
+    pub domain_id: u64,
+}
+
 #[allow(dead_code)]
 #[ext_contract(mpc_contract)]
 trait MPCContract {
     fn sign(&self, request: SignRequest);
+    fn experimental_signature_deposit(&self) -> U128;
+}
+
+#[allow(dead_code)]
+#[ext_contract(mpc_contract_v2)]
+trait MPCContractV2 {
+    fn sign(&self, request: SignRequestV2);
 }
 
 #[allow(dead_code)]
 #[ext_contract(this_contract)]
 trait ThisContract {
-    fn sign_callback(&self, nonce: u64, tx_type: u8, ethereum_tx: EVMTransaction) -> Vec<u8>;
-    fn sign_crosschain_balance_callback(&self) -> Vec<u8>;
-    fn sign_generic_callback(&self, ethereum_tx: EVMTransaction) -> Vec<u8>;
+    fn sign_batch_callback(
+        &self,
+        nonce: u64,
+        entries: Vec<BatchEntry>,
+        deposit: SignatureDeposit,
+    ) -> Vec<Vec<u8>>;
+    fn sign_callback(
+        &self,
+        nonce: u64,
+        tx_type: u8,
+        ethereum_tx: EVMTransaction,
+        deposit: SignatureDeposit,
+    ) -> Vec<u8>;
+    fn sign_crosschain_balance_callback(
+        &self,
+        payload_hash: [u8; 32],
+        deposit: SignatureDeposit,
+    ) -> Vec<u8>;
+    fn sign_replacement_callback(
+        &self,
+        nonce: u64,
+        tx_type: u8,
+        ethereum_tx: EVMTransaction,
+        deposit: SignatureDeposit,
+    ) -> Vec<u8>;
+    fn sync_signature_deposit_callback(&self) -> NearToken;
 }
@@ -1,12 +1,20 @@
+use crate::errors::{ensure, ContractError, OrContractError};
 use crate::{
-    constants::{KEY_VERSION, PATH},
+    constants::{
+        DEFAULT_CONFIG_TIMELOCK_MS, DEFAULT_FEE_BUMP_CEILING_BPS, DEFAULT_SESSION_TIMEOUT_MS,
+        STATE_VERSION,
+    },
     external::this_contract,
-    types::{ActiveSession, ActivityLog, CacheKey, ChainConfig, ChainId, Config, Step, Worker},
+    migrations::ActivityLogV0,
+    types::{
+        ActiveSession, ActivityLog, CacheKey, ChainConfig, ChainId, Config, Flow, OwnerProposal,
+        PendingConfigChange, Role, SignatureDeposit, SignerConfig, Step, TransactionParams, Worker,
+    },
 };
 use near_sdk::{
     env, near,
     store::{IterableMap, IterableSet, LookupMap},
-    AccountId, Gas, PanicOnDefault, Promise,
+    AccountId, Gas, NearToken, PanicOnDefault, PromiseOrValue,
 };
 use omni_transaction::evm::EVMTransaction;
 
@@ -14,12 +22,21 @@ mod access_control;
 mod admin;
 mod agent;
 mod allowances;
+mod batch_signing;
 mod callbacks;
 mod collateral;
+mod config_changes;
 mod constants;
+mod derivation;
 mod ecdsa;
 mod encoders;
+mod errors;
+mod events;
 mod external;
+mod migrations;
+mod ownership;
+mod pause;
+mod roles;
 mod snapshot_signing;
 mod state_machine;
 mod steps;
@@ -39,30 +56,87 @@ pub struct Contract {
     pub logs_nonce: u64,
     pub supported_chains: Vec<ChainId>,
     pub active_session: Option<ActiveSession>,
+    pub session_timeout_ms: u64,
     pub signatures_by_nonce_and_type: LookupMap<CacheKey, Vec<u8>>, // (nonce, tx_type) -> signed RLP prefixed (tx_type || rlp)
     pub payload_hashes_by_nonce_and_type: LookupMap<CacheKey, [u8; 32]>, // (nonce, tx_type) -> payload_hash (build_for_signing)
+    pub next_evm_nonce_by_chain: LookupMap<ChainId, u64>, // chain_id -> next nonce of the agent address
+    pub transactions_by_nonce_and_type: LookupMap<CacheKey, TransactionParams>, // (nonce, tx_type) -> signed tx fields
+    pub fee_bump_ceiling_bps: u128,
+    pub require_step_confirmations: bool, // next step waits for the previous one to be confirmed on chain
+    pub enforce_worker_codehash: bool,
+    pub roles_by_account_id: IterableMap<AccountId, Vec<Role>>,
+    pub paused: bool,
+    pub paused_chains: IterableSet<ChainId>,
+    pub paused_flows: Vec<Flow>,
+    pub pending_owner: Option<OwnerProposal>,
+    pub disabled_chains: IterableSet<ChainId>, // @dev kept in supported_chains but refused for new sessions
+    pub pending_config_changes: IterableMap<u64, PendingConfigChange>,
+    pub config_change_nonce: u64,
+    pub config_timelock_ms: u64,
+    pub state_version: u32,   // @dev bumped by migrate, see migrations/
+    pub signer: SignerConfig, // @dev new fields go last so older layouts stay a prefix of this one
+    pub original_fees_by_nonce_and_type: LookupMap<CacheKey, (u128, u128)>, // (nonce, tx_type) -> (max_fee_per_gas, max_priority_fee_per_gas) first signed
+    pub v0_logs: IterableMap<u64, ActivityLogV0>, // @dev logs written before state_version existed, drained by migrate_v0_logs
 }
 
 #[near]
 impl Contract {
     #[init]
     #[private]
-    pub fn init(source_chain: ChainId, configs: Vec<ChainConfig>) -> Self {
+    pub fn init(
+        source_chain: ChainId,
+        configs: Vec<ChainConfig>,
+        signer: Option<SignerConfig>,
+    ) -> Self {
         let owner_id = env::predecessor_account_id();
 
+        ensure!(
+            configs.iter().any(|cfg| cfg.chain_id == source_chain),
+            ContractError::SourceChainNotConfigured
+        );
+        for (index, cfg) in configs.iter().enumerate() {
+            ensure!(
+                !configs[..index]
+                    .iter()
+                    .any(|other| other.chain_id == cfg.chain_id),
+                ContractError::ChainAlreadySupported
+            );
+            cfg.config.validate(cfg.chain_id == source_chain);
+        }
+
         let mut contract = Self {
             owner_id,
             approved_codehashes: IterableSet::new(b"a"),
             worker_by_account_id: IterableMap::new(b"b"),
             config: LookupMap::new(b"c"),
             source_chain,
-            logs: IterableMap::new(b"e"),
+            logs: IterableMap::new(b"o"),
             logs_nonce: 0,
             active_session: None,
+            session_timeout_ms: DEFAULT_SESSION_TIMEOUT_MS,
             supported_chains: configs.iter().map(|cfg| cfg.chain_id.clone()).collect(),
             signatures_by_nonce_and_type: LookupMap::new(b"f"),
             payload_hashes_by_nonce_and_type: LookupMap::new(b"g"),
+            next_evm_nonce_by_chain: LookupMap::new(b"h"),
+            transactions_by_nonce_and_type: LookupMap::new(b"i"),
+            fee_bump_ceiling_bps: DEFAULT_FEE_BUMP_CEILING_BPS,
+            require_step_confirmations: false,
+            enforce_worker_codehash: true,
+            roles_by_account_id: IterableMap::new(b"j"),
+            paused: false,
+            paused_chains: IterableSet::new(b"k"),
+            paused_flows: vec![],
+            pending_owner: None,
+            disabled_chains: IterableSet::new(b"l"),
+            pending_config_changes: IterableMap::new(b"m"),
+            config_change_nonce: 0,
+            config_timelock_ms: DEFAULT_CONFIG_TIMELOCK_MS,
+            state_version: STATE_VERSION,
+            signer: signer.unwrap_or_else(|| SignerConfig::default_for(&env::current_account_id())),
+            original_fees_by_nonce_and_type: LookupMap::new(b"n"),
+            v0_logs: IterableMap::new(b"e"),
         };
+        derivation::root_public_key_to_point(&contract.signer.root_public_key);
         for cfg in configs {
             contract.config.insert(cfg.chain_id, cfg.config);
         }
@@ -75,30 +149,40 @@ impl Contract {
         step: Step,
         tx: EVMTransaction,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_step_is_next(step);
-
+    ) -> PromiseOrValue<Vec<u8>> {
         let nonce = self.get_active_session().nonce;
         let payload_hash = self.hash_payload(&tx);
         let key = CacheKey::new(nonce, step as u8);
 
-        if let Some(prev) = self.payload_hashes_by_nonce_and_type.get(&key) {
-            if *prev == payload_hash {
-                env::panic_str("Signature already cached for this step");
-                // TODO: Fix this to return the existing signature
-                // let hashed_signature = self
-                //     .signatures_by_nonce_and_type
-                //     .get(&key)
-                //     .expect("Signature must be present if payload hash matches");
-
-                // return PromiseOrValue::Value(hashed_signature.clone());
-            }
+        // @dev re-requesting an already signed step with the same payload returns the cached signature
+        if self.payload_hashes_by_nonce_and_type.get(&key) == Some(&payload_hash) {
+            let signed_payload = self
+                .signatures_by_nonce_and_type
+                .get(&key)
+                .or_error(ContractError::MissingCachedSignature);
+
+            // @dev nothing is requested from the signer, so the whole deposit goes back
+            SignatureDeposit::collect(NearToken::from_yoctonear(0)).refund(false);
+            return PromiseOrValue::Value(signed_payload.clone());
         }
 
-        ecdsa::get_sig(payload_hash, PATH.to_string(), KEY_VERSION).then(
-            this_contract::ext(env::current_account_id())
-                .with_static_gas(Gas::from_tgas(callback_gas_tgas))
-                .sign_callback(nonce, step as u8, tx),
+        self.assert_session_not_expired();
+        self.assert_flow_not_paused(&self.get_active_session().flow);
+        self.assert_chain_not_paused(tx.chain_id);
+        self.assert_step_is_next(step);
+        self.assert_valid_partial_transaction(
+            &tx,
+            self.get_chain_id_from_the_step_and_current_session(step),
+        );
+        let deposit = SignatureDeposit::collect(self.signer.signature_deposit);
+        self.reserve_evm_nonce(tx.chain_id, tx.nonce, false);
+
+        PromiseOrValue::Promise(
+            ecdsa::get_sig(payload_hash, &self.signer).then(
+                this_contract::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(callback_gas_tgas))
+                    .sign_callback(nonce, step as u8, tx, deposit),
+            ),
         )
     }
 }
@@ -106,9 +190,17 @@ impl Contract {
 #[cfg(test)]
 mod test_helpers {
     use super::types::*;
-    use super::Contract;
-    use near_sdk::NearToken;
-    use near_sdk::{test_utils::VMContextBuilder, testing_env};
+    use super::{derivation, Contract};
+    use crate::constants::DEFAULT_SIGNATURE_DEPOSIT;
+    use k256::ecdsa::SigningKey;
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use k256::elliptic_curve::PrimeField;
+    use k256::{ProjectivePoint, Scalar};
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+    use near_sdk::{env, AccountId, CurveType, NearToken, PublicKey};
+    use omni_transaction::evm::EVMTransaction;
     use omni_transaction::signer::types::{
         SerializableAffinePoint, SerializableScalar, SignatureResponse,
     };
@@ -116,7 +208,8 @@ mod test_helpers {
 
     pub const ONE_NEAR: NearToken = NearToken::from_near(1);
     pub const OWNER: &str = "owner.testnet";
-    pub const _WORKER: &str = "worker.testnet";
+    pub const WORKER: &str = "worker.testnet";
+    pub const WORKER_CODEHASH: &str = "fake-codehash";
     pub const DEFAULT_ATTACHED_DEPOSIT: NearToken = ONE_NEAR;
 
     pub fn set_context(predecessor: &str) {
@@ -127,6 +220,15 @@ mod test_helpers {
         testing_env!(builder.build());
     }
 
+    pub fn set_context_with_timestamp(predecessor: &str, timestamp_ms: u64) {
+        let mut builder = VMContextBuilder::new();
+        builder.predecessor_account_id(predecessor.parse().unwrap());
+        builder.attached_deposit(DEFAULT_ATTACHED_DEPOSIT);
+        builder.block_timestamp(timestamp_ms * 1_000_000);
+
+        testing_env!(builder.build());
+    }
+
     pub fn set_context_with_attached_deposit(predecessor: &str, amount: NearToken) {
         let mut builder = VMContextBuilder::new();
         builder.predecessor_account_id(predecessor.parse().unwrap());
@@ -140,8 +242,8 @@ mod test_helpers {
     pub const DEFAULT_SOURCE_CHAIN: ChainId = 1;
     pub const DEFAULT_DESTINATION_CHAIN: ChainId = 2;
 
-    fn init_contract_with(source_chain: ChainId, configs: Vec<ChainConfig>) -> Contract {
-        Contract::init(source_chain, configs)
+    pub fn init_contract_with(source_chain: ChainId, configs: Vec<ChainConfig>) -> Contract {
+        Contract::init(source_chain, configs, Some(build_test_signer()))
     }
 
     pub fn init_contract_with_defaults() -> Contract {
@@ -151,6 +253,46 @@ mod test_helpers {
         init_contract_with(source_chain, configs)
     }
 
+    pub fn register_worker(contract: &mut Contract, account_id: &str) {
+        contract
+            .approved_codehashes
+            .insert(WORKER_CODEHASH.to_string());
+        contract.worker_by_account_id.insert(
+            account_id.parse().unwrap(),
+            Worker {
+                checksum: "fake-checksum".to_string(),
+                codehash: WORKER_CODEHASH.to_string(),
+            },
+        );
+    }
+
+    pub fn init_contract_with_worker() -> Contract {
+        let mut contract = init_contract_with_defaults();
+        register_worker(&mut contract, WORKER);
+        contract
+    }
+
+    pub const DEFAULT_AMOUNT: u128 = 1_000_000_000;
+
+    // @dev expects the context to be set to WORKER
+    pub fn start_session_with_signed_steps(flow: Flow, steps: &[Step]) -> (Contract, u64) {
+        let mut contract = init_contract_with_worker();
+        // @dev deposits into the vault have to land on the vault chain
+        let (source_chain, destination_chain) = match flow {
+            Flow::AaveToRebalancer => (DEFAULT_DESTINATION_CHAIN, DEFAULT_SOURCE_CHAIN),
+            _ => (DEFAULT_SOURCE_CHAIN, DEFAULT_DESTINATION_CHAIN),
+        };
+        let nonce = contract.start_rebalance(flow, source_chain, destination_chain, DEFAULT_AMOUNT);
+
+        for &step in steps {
+            contract
+                .signatures_by_nonce_and_type
+                .insert(CacheKey::new(nonce, step as u8), vec![step as u8]);
+        }
+
+        (contract, nonce)
+    }
+
     // Utilities
 
     impl Contract {
@@ -170,25 +312,37 @@ mod test_helpers {
     //     assert!(contract.logs.len() == expected.logs.len());
     // }
 
-    fn build_fake_configs() -> Vec<ChainConfig> {
+    pub fn build_fake_configs() -> Vec<ChainConfig> {
         vec![
             ChainConfig {
                 chain_id: DEFAULT_SOURCE_CHAIN,
                 config: Config {
                     rebalancer: RebalancerConfig {
-                        vault_address: "0xE168d95f8d1B8EC167A63c8E696076EC8EE95337".to_string(),
+                        vault_address: "0xE168d95f8d1B8EC167A63c8E696076EC8EE95337"
+                            .parse()
+                            .unwrap(),
                     },
                     cctp: CCTPConfig {
-                        messenger_address: "0x8FE6B999Dc680CcFDD5Bf7EB0974218be2542DAA".to_string(),
+                        messenger_address: "0x8FE6B999Dc680CcFDD5Bf7EB0974218be2542DAA"
+                            .parse()
+                            .unwrap(),
                         transmitter_address: "0xe737e5cebeeba77efe34d4aa090756590b1ce275"
-                            .to_string(),
-                        usdc_address: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d".to_string(),
+                            .parse()
+                            .unwrap(),
+                        usdc_address: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d"
+                            .parse()
+                            .unwrap(),
                     },
                     aave: AaveConfig {
-                        asset: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d".to_string(),
+                        asset: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d"
+                            .parse()
+                            .unwrap(),
                         lending_pool_address: "0xBfC91D59fdAA134A4ED45f7B584cAf96D7792Eff"
-                            .to_string(),
-                        on_behalf_of: "0x20f2747bbc52453ac0774b5b2fe0e28dc6637f30".to_string(),
+                            .parse()
+                            .unwrap(),
+                        on_behalf_of: "0x20f2747bbc52453ac0774b5b2fe0e28dc6637f30"
+                            .parse()
+                            .unwrap(),
                         referral_code: 0,
                     },
                 },
@@ -197,17 +351,29 @@ mod test_helpers {
                 chain_id: DEFAULT_DESTINATION_CHAIN,
                 config: Config {
                     rebalancer: RebalancerConfig {
-                        vault_address: "".into(),
+                        vault_address: EvmAddress::ZERO,
                     },
                     cctp: CCTPConfig {
-                        messenger_address: "0x8FE6B999Dc680CcFDD5Bf7EB0974218be2542DAA".into(),
-                        transmitter_address: "0xe737e5cebeeba77efe34d4aa090756590b1ce275".into(),
-                        usdc_address: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d".into(),
+                        messenger_address: "0x8FE6B999Dc680CcFDD5Bf7EB0974218be2542DAA"
+                            .parse()
+                            .unwrap(),
+                        transmitter_address: "0xe737e5cebeeba77efe34d4aa090756590b1ce275"
+                            .parse()
+                            .unwrap(),
+                        usdc_address: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d"
+                            .parse()
+                            .unwrap(),
                     },
                     aave: AaveConfig {
-                        asset: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d".into(),
-                        lending_pool_address: "0xBfC91D59fdAA134A4ED45f7B584cAf96D7792Eff".into(),
-                        on_behalf_of: "0x20f2747bbc52453ac0774b5b2fe0e28dc6637f30".into(),
+                        asset: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d"
+                            .parse()
+                            .unwrap(),
+                        lending_pool_address: "0xBfC91D59fdAA134A4ED45f7B584cAf96D7792Eff"
+                            .parse()
+                            .unwrap(),
+                        on_behalf_of: "0x20f2747bbc52453ac0774b5b2fe0e28dc6637f30"
+                            .parse()
+                            .unwrap(),
                         referral_code: 0,
                     },
                 },
@@ -215,7 +381,94 @@ mod test_helpers {
         ]
     }
 
-    fn build_mock_signature() -> SignatureResponse {
+    pub fn build_partial_tx() -> EVMTransaction {
+        EVMTransaction {
+            chain_id: 1,
+            nonce: 1,
+            to: None,
+            input: vec![],
+            value: 0,
+            gas_limit: 100,
+            max_fee_per_gas: 100,
+            max_priority_fee_per_gas: 100,
+            access_list: vec![],
+        }
+    }
+
+    // @dev stands in for the MPC network so callbacks can be fed signatures that verify
+    pub const TEST_MPC_ROOT_SECRET: [u8; 32] = [0x42; 32];
+
+    fn test_mpc_root_secret() -> Scalar {
+        Scalar::from_repr(TEST_MPC_ROOT_SECRET.into()).unwrap()
+    }
+
+    pub fn build_test_signer() -> SignerConfig {
+        let root = (ProjectivePoint::GENERATOR * test_mpc_root_secret()).to_affine();
+        let root_public_key = PublicKey::from_parts(
+            CurveType::SECP256K1,
+            root.to_encoded_point(false).as_bytes()[1..].to_vec(),
+        )
+        .unwrap();
+
+        SignerConfig {
+            root_public_key,
+            ..SignerConfig::default_for(&env::current_account_id())
+        }
+    }
+
+    // @dev expects the contract to use the test signer
+    pub fn build_signature(contract: &Contract, payload_hash: [u8; 32]) -> SignatureResponse {
+        let epsilon = derivation::derive_epsilon(&env::current_account_id(), &contract.signer.path);
+        let signing_key =
+            SigningKey::from_bytes(&(test_mpc_root_secret() + epsilon).to_bytes()).unwrap();
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&payload_hash).unwrap();
+        let (r, s) = signature.split_bytes();
+
+        let mut big_r = vec![0x02 | recovery_id.is_y_odd() as u8];
+        big_r.extend_from_slice(&r);
+        SignatureResponse {
+            big_r: SerializableAffinePoint {
+                affine_point: hex::encode(big_r),
+            },
+            s: SerializableScalar {
+                scalar: hex::encode(s),
+            },
+            recovery_id: recovery_id.to_byte(),
+        }
+    }
+
+    pub fn build_tx_signature(contract: &Contract, tx: &EVMTransaction) -> SignatureResponse {
+        build_signature(contract, contract.hash_payload(tx))
+    }
+
+    pub fn build_signature_deposit() -> SignatureDeposit {
+        SignatureDeposit {
+            payer: WORKER.parse().unwrap(),
+            attached: DEFAULT_ATTACHED_DEPOSIT,
+            required: DEFAULT_SIGNATURE_DEPOSIT,
+        }
+    }
+
+    pub fn get_created_transfers() -> Vec<(AccountId, NearToken)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt
+                    .actions
+                    .into_iter()
+                    .filter_map(move |action| match action {
+                        MockAction::Transfer { deposit, .. } => {
+                            Some((receiver_id.clone(), deposit))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
+    // @dev does not recover to any agent key
+    pub fn build_mock_signature() -> SignatureResponse {
         SignatureResponse {
             big_r: SerializableAffinePoint {
                 affine_point: hex::encode(vec![0u8; 33]),
@@ -258,17 +511,17 @@ mod test_helpers {
 
     impl AaveConfig {
         pub fn with_asset(mut self, asset: &str) -> Self {
-            self.asset = asset.to_string();
+            self.asset = asset.parse().unwrap();
             self
         }
 
         pub fn with_lending_pool_address(mut self, address: &str) -> Self {
-            self.lending_pool_address = address.to_string();
+            self.lending_pool_address = address.parse().unwrap();
             self
         }
 
         pub fn with_on_behalf_of(mut self, on_behalf_of: &str) -> Self {
-            self.on_behalf_of = on_behalf_of.to_string();
+            self.on_behalf_of = on_behalf_of.parse().unwrap();
             self
         }
 
@@ -280,24 +533,24 @@ mod test_helpers {
 
     impl RebalancerConfig {
         pub fn with_vault_address(mut self, address: &str) -> Self {
-            self.vault_address = address.to_string();
+            self.vault_address = address.parse().unwrap();
             self
         }
     }
 
     impl CCTPConfig {
         pub fn with_messenger_address(mut self, address: &str) -> Self {
-            self.messenger_address = address.to_string();
+            self.messenger_address = address.parse().unwrap();
             self
         }
 
         pub fn with_transmitter_address(mut self, address: &str) -> Self {
-            self.transmitter_address = address.to_string();
+            self.transmitter_address = address.parse().unwrap();
             self
         }
 
         pub fn with_usdc_address(mut self, address: &str) -> Self {
-            self.usdc_address = address.to_string();
+            self.usdc_address = address.parse().unwrap();
             self
         }
     }
@@ -305,8 +558,11 @@ mod test_helpers {
 
 #[cfg(test)]
 mod maintests {
+    use crate::constants::DEFAULT_SESSION_TIMEOUT_MS;
     use crate::test_helpers::*;
-    use near_sdk::AccountId;
+    use crate::types::*;
+    use crate::Contract;
+    use near_sdk::{AccountId, NearToken, PromiseOrValue};
 
     use std::str::FromStr;
 
@@ -326,7 +582,157 @@ mod maintests {
         assert_eq!(contract.supported_chains[0], DEFAULT_SOURCE_CHAIN);
         assert_eq!(contract.supported_chains[1], DEFAULT_DESTINATION_CHAIN);
         assert!(contract.active_session.is_none());
+        assert_eq!(contract.session_timeout_ms, DEFAULT_SESSION_TIMEOUT_MS);
+        assert!(contract.enforce_worker_codehash);
         assert!(contract.config.contains_key(&DEFAULT_SOURCE_CHAIN));
         assert!(contract.config.contains_key(&DEFAULT_DESTINATION_CHAIN));
     }
+
+    #[test]
+    #[should_panic(expected = "Source chain not configured")]
+    fn fails_to_init_without_source_chain_config() {
+        set_context(OWNER);
+
+        init_contract_with(42, build_fake_configs());
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain already supported")]
+    fn fails_to_init_with_duplicated_chain() {
+        set_context(OWNER);
+
+        let mut configs = build_fake_configs();
+        configs[1].chain_id = DEFAULT_SOURCE_CHAIN;
+        init_contract_with(DEFAULT_SOURCE_CHAIN, configs);
+    }
+
+    #[test]
+    #[should_panic(expected = "CCTP usdc_address must match aave.asset")]
+    fn fails_to_init_with_usdc_not_matching_aave_asset() {
+        set_context(OWNER);
+
+        let mut configs = build_fake_configs();
+        configs[1].config.cctp.usdc_address = "0x5fd84259d66Cd46123540766Be93DFE6D43130D7"
+            .parse()
+            .unwrap();
+        init_contract_with(DEFAULT_SOURCE_CHAIN, configs);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid vault")]
+    fn fails_to_init_without_source_chain_vault() {
+        set_context(OWNER);
+
+        let mut configs = build_fake_configs();
+        configs[0].config.rebalancer.vault_address = EvmAddress::ZERO;
+        init_contract_with(DEFAULT_SOURCE_CHAIN, configs);
+    }
+
+    #[test]
+    fn test_trigger_signature_returns_cached_signature() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let nonce = contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            1_000,
+        );
+
+        let tx = build_partial_tx();
+        let key = CacheKey::new(nonce, Step::AaveWithdraw as u8);
+        let signed_payload = vec![Step::AaveWithdraw as u8, 0xde, 0xad];
+
+        contract
+            .payload_hashes_by_nonce_and_type
+            .insert(key.clone(), contract.hash_payload(&tx));
+        contract
+            .signatures_by_nonce_and_type
+            .insert(key, signed_payload.clone());
+
+        match contract.trigger_signature(Step::AaveWithdraw, tx, 10) {
+            PromiseOrValue::Value(cached) => assert_eq!(cached, signed_payload),
+            PromiseOrValue::Promise(_) => panic!("Expected the cached signature"),
+        }
+    }
+
+    #[test]
+    fn test_trigger_signature_refunds_deposit_for_cached_signature() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        let tx = build_partial_tx();
+        let key = CacheKey::new(nonce, Step::AaveWithdraw as u8);
+        contract
+            .payload_hashes_by_nonce_and_type
+            .insert(key.clone(), contract.hash_payload(&tx));
+        contract
+            .signatures_by_nonce_and_type
+            .insert(key, vec![Step::AaveWithdraw as u8]);
+
+        contract.trigger_signature(Step::AaveWithdraw, tx, 10);
+
+        assert_eq!(
+            get_created_transfers(),
+            vec![(WORKER.parse().unwrap(), DEFAULT_ATTACHED_DEPOSIT)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit does not cover the signature deposit")]
+    fn fails_if_signature_deposit_is_not_covered() {
+        set_context_with_attached_deposit(WORKER, NearToken::from_yoctonear(1));
+
+        start_session_and_sign_withdraw(None, 7);
+    }
+
+    fn start_session_and_sign_withdraw(next_nonce: Option<u64>, tx_nonce: u64) -> Contract {
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        if let Some(next_nonce) = next_nonce {
+            contract
+                .next_evm_nonce_by_chain
+                .insert(DEFAULT_SOURCE_CHAIN, next_nonce);
+        }
+
+        let mut tx = build_partial_tx();
+        tx.nonce = tx_nonce;
+        contract.trigger_signature(Step::AaveWithdraw, tx, 10);
+
+        contract
+    }
+
+    #[test]
+    fn test_trigger_signature_reserves_nonce() {
+        set_context(WORKER);
+
+        let contract = start_session_and_sign_withdraw(None, 7);
+
+        assert_eq!(contract.get_next_nonce(DEFAULT_SOURCE_CHAIN), Some(8));
+        assert_eq!(contract.get_next_nonce(DEFAULT_DESTINATION_CHAIN), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nonce already used")]
+    fn fails_if_nonce_is_reused() {
+        set_context(WORKER);
+
+        start_session_and_sign_withdraw(Some(8), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nonce gap")]
+    fn fails_if_nonce_skips_ahead() {
+        set_context(WORKER);
+
+        start_session_and_sign_withdraw(Some(8), 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nonce overflow")]
+    fn fails_if_nonce_overflows() {
+        set_context(WORKER);
+
+        start_session_and_sign_withdraw(None, u64::MAX);
+    }
 }
@@ -0,0 +1,409 @@
+use near_sdk::borsh::BorshDeserialize;
+use near_sdk::serde_json::json;
+use near_sdk::store::{IterableMap, IterableSet, LookupMap};
+use near_sdk::{env, near, NearToken, Promise};
+
+use crate::constants::{
+    DEFAULT_CONFIG_TIMELOCK_MS, DEFAULT_FEE_BUMP_CEILING_BPS, DEFAULT_SESSION_TIMEOUT_MS,
+    MIGRATE_GAS, STATE_VERSION,
+};
+use crate::events::emit_event;
+use crate::types::{ChainId, SignerConfig};
+use crate::{Contract, ContractExt};
+
+mod v0;
+
+use crate::errors::{ensure, ContractError, OrContractError};
+pub use v0::*;
+
+const STATE_KEY: &[u8] = b"STATE";
+
+#[near]
+impl Contract {
+    // @dev the new wasm is the raw input of the call, not a JSON argument
+    pub fn update_contract(&mut self) -> Promise {
+        self.require_owner();
+        let code = env::input().or_error(ContractError::MissingContractCode);
+        ensure!(!code.is_empty(), ContractError::MissingContractCode);
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                "migrate".to_string(),
+                vec![],
+                NearToken::from_yoctonear(0),
+                MIGRATE_GAS,
+            )
+    }
+
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(STATE_KEY).or_error(ContractError::NoStateToMigrate);
+
+        // @dev v0 is the only layout without a state_version. A newer layout starts with this one,
+        // so it is read as a prefix to tell a downgrade apart from an unknown layout
+        let mut rest = state.as_slice();
+        let (mut contract, from_version) = match Contract::deserialize(&mut rest) {
+            Ok(contract) => {
+                ensure!(
+                    contract.state_version <= STATE_VERSION,
+                    ContractError::CannotDowngradeState
+                );
+                ensure!(rest.is_empty(), ContractError::UnknownStateLayout);
+                let from_version = contract.state_version;
+                (contract, from_version)
+            }
+            Err(_) => (
+                Self::from_v0(
+                    ContractV0::try_from_slice(&state).or_error(ContractError::UnknownStateLayout),
+                ),
+                0,
+            ),
+        };
+
+        contract.state_version = STATE_VERSION;
+
+        emit_event(
+            "contract_migrated",
+            json!({
+                "from_version": from_version,
+                "to_version": STATE_VERSION,
+                "pending_v0_logs": contract.v0_logs.len(),
+            }),
+        );
+
+        contract
+    }
+
+    // @dev v0 logs are rewritten in batches after the upgrade so migrate stays within its gas,
+    // returns how many are left
+    pub fn migrate_v0_logs(&mut self, limit: u32) -> u32 {
+        self.require_owner();
+
+        let nonces: Vec<u64> = self.v0_logs.keys().take(limit as usize).copied().collect();
+        for &nonce in &nonces {
+            let log = self
+                .v0_logs
+                .remove(&nonce)
+                .or_error(ContractError::LogNotFound);
+            self.logs.insert(nonce, log.migrate());
+        }
+
+        emit_event(
+            "v0_logs_migrated",
+            json!({ "nonces": nonces, "remaining": self.v0_logs.len() }),
+        );
+
+        self.v0_logs.len()
+    }
+
+    pub fn get_state_version(&self) -> u32 {
+        self.state_version
+    }
+}
+
+impl Contract {
+    // @dev v0 stored chain config addresses as strings, entries are rewritten under the same prefix
+    fn migrate_v0_configs(&mut self) {
+        let old_configs: LookupMap<ChainId, ConfigV0> = LookupMap::new(b"c");
+        for chain_id in self.supported_chains.clone() {
+            if let Some(old) = old_configs.get(&chain_id) {
+                self.config.insert(chain_id, old.clone().migrate());
+            }
+        }
+    }
+
+    // @dev V0 did not record the session worker and its flows had no approve steps, an active
+    // session could not be resumed safely, so it has to be completed before upgrading
+    fn from_v0(old: ContractV0) -> Self {
+        let ContractV0 {
+            owner_id,
+            source_chain,
+            approved_codehashes,
+            worker_by_account_id,
+            config,
+            logs: v0_logs,
+            logs_nonce,
+            supported_chains,
+            active_session,
+            signatures_by_nonce_and_type,
+            payload_hashes_by_nonce_and_type,
+        } = old;
+        ensure!(
+            active_session.is_none(),
+            ContractError::MigrationWithActiveSession
+        );
+
+        let mut contract = Self {
+            owner_id,
+            source_chain,
+            approved_codehashes,
+            worker_by_account_id,
+            config,
+            logs: IterableMap::new(b"o"),
+            logs_nonce,
+            supported_chains,
+            active_session: None,
+            session_timeout_ms: DEFAULT_SESSION_TIMEOUT_MS,
+            signatures_by_nonce_and_type,
+            payload_hashes_by_nonce_and_type,
+            next_evm_nonce_by_chain: LookupMap::new(b"h"),
+            transactions_by_nonce_and_type: LookupMap::new(b"i"),
+            fee_bump_ceiling_bps: DEFAULT_FEE_BUMP_CEILING_BPS,
+            require_step_confirmations: false,
+            enforce_worker_codehash: true,
+            roles_by_account_id: IterableMap::new(b"j"),
+            paused: false,
+            paused_chains: IterableSet::new(b"k"),
+            paused_flows: vec![],
+            pending_owner: None,
+            disabled_chains: IterableSet::new(b"l"),
+            pending_config_changes: IterableMap::new(b"m"),
+            config_change_nonce: 0,
+            config_timelock_ms: DEFAULT_CONFIG_TIMELOCK_MS,
+            state_version: 0,
+            signer: SignerConfig::default_for(&env::current_account_id()),
+            original_fees_by_nonce_and_type: LookupMap::new(b"n"),
+            v0_logs,
+        };
+        contract.migrate_v0_configs();
+        contract
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use super::*;
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+    use omni_transaction::evm::types::Signature;
+
+    fn v0_config(vault_address: &str) -> ConfigV0 {
+        ConfigV0 {
+            aave: AaveConfigV0 {
+                asset: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d".to_string(),
+                on_behalf_of: "0x20f2747bbc52453ac0774b5b2fe0e28dc6637f30".to_string(),
+                referral_code: 0,
+                lending_pool_address: "0xBfC91D59fdAA134A4ED45f7B584cAf96D7792Eff".to_string(),
+            },
+            cctp: CCTPConfigV0 {
+                messenger_address: "0x8FE6B999Dc680CcFDD5Bf7EB0974218be2542DAA".to_string(),
+                transmitter_address: "0xe737e5cebeeba77efe34d4aa090756590b1ce275".to_string(),
+                usdc_address: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d".to_string(),
+            },
+            rebalancer: RebalancerConfigV0 {
+                vault_address: vault_address.to_string(),
+            },
+        }
+    }
+
+    // @dev tx_type || signed EIP-1559 rlp, as the v0 sign_callback stored it
+    fn v0_signed_payload(step: Step, chain_id: ChainId, nonce: u64) -> Vec<u8> {
+        let mut tx = build_partial_tx();
+        tx.chain_id = chain_id;
+        tx.nonce = nonce;
+
+        let mut payload = vec![step as u8];
+        payload.extend(tx.build_with_signature(&Signature {
+            v: 0,
+            r: vec![1; 32],
+            s: vec![2; 32],
+        }));
+        payload
+    }
+
+    fn write_v0_state(with_active_session: bool) {
+        let mut configs: LookupMap<ChainId, ConfigV0> = LookupMap::new(b"c");
+        configs.insert(
+            DEFAULT_SOURCE_CHAIN,
+            v0_config("0xE168d95f8d1B8EC167A63c8E696076EC8EE95337"),
+        );
+        configs.insert(DEFAULT_DESTINATION_CHAIN, v0_config(""));
+        configs.flush();
+
+        let mut logs: IterableMap<u64, ActivityLogV0> = IterableMap::new(b"e");
+        for nonce in 0..2 {
+            logs.insert(
+                nonce,
+                ActivityLogV0 {
+                    activity_type: AgentActionType::Rebalance,
+                    source_chain: DEFAULT_SOURCE_CHAIN,
+                    destination_chain: DEFAULT_DESTINATION_CHAIN,
+                    timestamp: 1_000,
+                    nonce,
+                    amount: DEFAULT_AMOUNT,
+                    transactions: vec![
+                        v0_signed_payload(Step::AaveWithdraw, DEFAULT_SOURCE_CHAIN, 7),
+                        v0_signed_payload(Step::CCTPMint, DEFAULT_DESTINATION_CHAIN, 300),
+                    ],
+                },
+            );
+        }
+        logs.flush();
+
+        let v0 = ContractV0 {
+            owner_id: OWNER.parse().unwrap(),
+            source_chain: DEFAULT_SOURCE_CHAIN,
+            approved_codehashes: IterableSet::new(b"a"),
+            worker_by_account_id: IterableMap::new(b"b"),
+            config: LookupMap::new(b"c"),
+            logs,
+            logs_nonce: 2,
+            supported_chains: vec![DEFAULT_SOURCE_CHAIN, DEFAULT_DESTINATION_CHAIN],
+            active_session: with_active_session.then(|| ActiveSessionV0 {
+                nonce: 1,
+                flow: Flow::AaveToAave,
+                started_at: 1_000,
+            }),
+            signatures_by_nonce_and_type: LookupMap::new(b"f"),
+            payload_hashes_by_nonce_and_type: LookupMap::new(b"g"),
+        };
+        env::state_write(&v0);
+    }
+
+    #[test]
+    fn test_migrate_from_v0() {
+        set_context(OWNER);
+        write_v0_state(false);
+
+        let contract = Contract::migrate();
+
+        assert_eq!(contract.get_state_version(), STATE_VERSION);
+        assert_eq!(contract.logs_nonce, 2);
+        assert!(contract.active_session.is_none());
+        assert_eq!(contract.session_timeout_ms, DEFAULT_SESSION_TIMEOUT_MS);
+        assert_eq!(contract.v0_logs.len(), 2);
+        assert_eq!(contract.get_latest_logs(10).len(), 2);
+
+        let source = contract.get_chain_config(&DEFAULT_SOURCE_CHAIN);
+        assert_eq!(
+            source.rebalancer.vault_address.to_string(),
+            "0xE168d95f8d1B8EC167A63c8E696076EC8EE95337"
+        );
+        assert_eq!(source.cctp.usdc_address, source.aave.asset);
+        assert!(contract
+            .get_chain_config(&DEFAULT_DESTINATION_CHAIN)
+            .rebalancer
+            .vault_address
+            .is_zero());
+    }
+
+    #[test]
+    fn test_migrate_v0_logs_in_batches() {
+        set_context(OWNER);
+        write_v0_state(false);
+
+        let mut contract = Contract::migrate();
+
+        assert_eq!(contract.migrate_v0_logs(1), 1);
+        assert_eq!(contract.logs.len(), 1);
+        assert_eq!(contract.migrate_v0_logs(10), 0);
+        assert_eq!(contract.logs.len(), 2);
+
+        let log = contract.logs.get(&1).unwrap();
+        assert_eq!(log.status, ActivityStatus::Completed);
+        assert_eq!(log.transactions.len(), 2);
+        assert_eq!(log.steps.len(), 2);
+        assert_eq!(log.steps[0].step, Step::AaveWithdraw);
+        assert_eq!(log.steps[0].chain_id, DEFAULT_SOURCE_CHAIN);
+        assert_eq!(log.steps[0].evm_nonce, 7);
+        assert_eq!(log.steps[1].step, Step::CCTPMint);
+        assert_eq!(log.steps[1].chain_id, DEFAULT_DESTINATION_CHAIN);
+        assert_eq!(log.steps[1].evm_nonce, 300);
+        assert!(log.steps.iter().all(|record| record.signed_at == 1_000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_to_migrate_v0_logs_if_not_owner() {
+        set_context(OWNER);
+        write_v0_state(false);
+
+        let mut contract = Contract::migrate();
+
+        set_context("random.near");
+        contract.migrate_v0_logs(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Complete the active session before migrating")]
+    fn fails_to_migrate_v0_with_active_session() {
+        set_context(OWNER);
+        write_v0_state(true);
+
+        Contract::migrate();
+    }
+
+    #[test]
+    fn test_migrate_current_state_keeps_it() {
+        set_context(OWNER);
+        let mut contract = init_contract_with_defaults();
+        contract.set_session_timeout(1_000);
+        env::state_write(&contract);
+
+        let migrated = Contract::migrate();
+
+        assert_eq!(migrated.get_state_version(), STATE_VERSION);
+        assert_eq!(migrated.session_timeout_ms, 1_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown contract state layout")]
+    fn fails_if_state_has_unknown_fields() {
+        set_context(OWNER);
+        let contract = init_contract_with_defaults();
+        let mut state = near_sdk::borsh::to_vec(&contract).unwrap();
+        state.push(0);
+        env::storage_write(STATE_KEY, &state);
+
+        Contract::migrate();
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot downgrade contract state")]
+    fn fails_to_migrate_a_newer_state() {
+        set_context(OWNER);
+        let mut contract = init_contract_with_defaults();
+        contract.state_version = STATE_VERSION + 1;
+        env::state_write(&contract);
+
+        Contract::migrate();
+    }
+
+    #[test]
+    fn test_update_contract_deploys_and_migrates() {
+        let mut builder = VMContextBuilder::new();
+        builder.predecessor_account_id(OWNER.parse().unwrap());
+        builder.input(vec![0x00, 0x61, 0x73, 0x6d]);
+        testing_env!(builder.build());
+
+        let mut contract = init_contract_with_defaults();
+        contract.update_contract();
+
+        let actions: Vec<MockAction> = get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .collect();
+        assert!(actions
+            .iter()
+            .any(|action| matches!(action, MockAction::DeployContract { .. })));
+        assert!(actions.iter().any(|action| matches!(
+            action,
+            MockAction::FunctionCallWeight { method_name, .. } if method_name == b"migrate"
+        )));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_to_update_if_not_owner() {
+        set_context(OWNER);
+        let mut contract = init_contract_with_defaults();
+
+        set_context("random.near");
+        contract.update_contract();
+    }
+}
@@ -0,0 +1,187 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::store::{IterableMap, IterableSet, LookupMap};
+use near_sdk::AccountId;
+
+use crate::errors::{ContractError, OrContractError};
+use crate::types::{
+    AaveConfig, ActivityLog, ActivityStatus, AgentActionType, CCTPConfig, CacheKey, ChainId,
+    Config, EvmAddress, Flow, PayloadType, RebalancerConfig, StepRecord, Worker,
+};
+
+// @dev layout of the first deployed version, before state_version existed
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct ActivityLogV0 {
+    pub activity_type: AgentActionType,
+    pub source_chain: ChainId,
+    pub destination_chain: ChainId,
+    pub timestamp: u64,
+    pub nonce: u64,
+    pub amount: u128,
+    pub transactions: Vec<Vec<u8>>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ActiveSessionV0 {
+    pub nonce: u64,
+    pub flow: Flow,
+    pub started_at: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV0 {
+    pub owner_id: AccountId,
+    pub source_chain: ChainId,
+    pub approved_codehashes: IterableSet<String>,
+    pub worker_by_account_id: IterableMap<AccountId, Worker>,
+    pub config: LookupMap<ChainId, Config>,
+    pub logs: IterableMap<u64, ActivityLogV0>,
+    pub logs_nonce: u64,
+    pub supported_chains: Vec<ChainId>,
+    pub active_session: Option<ActiveSessionV0>,
+    pub signatures_by_nonce_and_type: LookupMap<CacheKey, Vec<u8>>,
+    pub payload_hashes_by_nonce_and_type: LookupMap<CacheKey, [u8; 32]>,
+}
+
+// @dev chain configs in v0 stored addresses as unchecked strings
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct AaveConfigV0 {
+    pub asset: String,
+    pub on_behalf_of: String,
+    pub referral_code: u16,
+    pub lending_pool_address: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct CCTPConfigV0 {
+    pub messenger_address: String,
+    pub transmitter_address: String,
+    pub usdc_address: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct RebalancerConfigV0 {
+    pub vault_address: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub struct ConfigV0 {
+    pub aave: AaveConfigV0,
+    pub cctp: CCTPConfigV0,
+    pub rebalancer: RebalancerConfigV0,
+}
+
+// @dev chains without a vault were configured with an empty string. Stored values are lowercased
+// so a bad checksum already on chain cannot block the upgrade
+fn parse_address(value: &str, name: &'static str) -> EvmAddress {
+    if value.is_empty() {
+        return EvmAddress::ZERO;
+    }
+    value
+        .to_ascii_lowercase()
+        .parse()
+        .or_error(ContractError::InvalidAddress(name))
+}
+
+impl ConfigV0 {
+    pub fn migrate(self) -> Config {
+        Config {
+            aave: AaveConfig {
+                asset: parse_address(&self.aave.asset, "asset address"),
+                on_behalf_of: parse_address(&self.aave.on_behalf_of, "on_behalf_of address"),
+                referral_code: self.aave.referral_code,
+                lending_pool_address: parse_address(
+                    &self.aave.lending_pool_address,
+                    "lending pool",
+                ),
+            },
+            cctp: CCTPConfig {
+                messenger_address: parse_address(&self.cctp.messenger_address, "messenger"),
+                transmitter_address: parse_address(&self.cctp.transmitter_address, "transmitter"),
+                usdc_address: parse_address(&self.cctp.usdc_address, "USDC address"),
+            },
+            rebalancer: RebalancerConfig {
+                vault_address: parse_address(&self.rebalancer.vault_address, "vault"),
+            },
+        }
+    }
+}
+
+impl ActivityLogV0 {
+    // @dev only logs of finished sessions are migrated, V0 closed them through complete_rebalance
+    pub fn migrate(self) -> ActivityLog {
+        // @dev V0 did not record when a step was signed, the session start is the closest bound
+        let steps = self
+            .transactions
+            .iter()
+            .filter_map(|payload| {
+                let (&tx_type, signed_tx) = payload.split_first()?;
+                let (chain_id, evm_nonce) = chain_id_and_nonce(signed_tx)?;
+                Some(StepRecord {
+                    step: PayloadType::from(tx_type),
+                    signed_at: self.timestamp,
+                    chain_id,
+                    evm_nonce,
+                    confirmation: None,
+                })
+            })
+            .collect();
+
+        ActivityLog {
+            activity_type: self.activity_type,
+            source_chain: self.source_chain,
+            destination_chain: self.destination_chain,
+            timestamp: self.timestamp,
+            nonce: self.nonce,
+            amount: self.amount,
+            transactions: self.transactions,
+            superseded_transactions: vec![],
+            status: ActivityStatus::Completed,
+            steps,
+            skipped_steps: vec![],
+            failure_reason: None,
+            updated_at: self.timestamp,
+        }
+    }
+}
+
+// @dev V0 only signed EIP-1559 transactions, 0x02 || rlp([chain_id, nonce, ...])
+fn chain_id_and_nonce(signed_tx: &[u8]) -> Option<(ChainId, u64)> {
+    let (&envelope, rlp) = signed_tx.split_first()?;
+    if envelope != 0x02 {
+        return None;
+    }
+    let (fields, _) = rlp_item(rlp, 0xc0)?;
+    let (chain_id, rest) = rlp_item(fields, 0x80)?;
+    let (nonce, _) = rlp_item(rest, 0x80)?;
+    Some((be_u64(chain_id)?, be_u64(nonce)?))
+}
+
+// @dev splits the payload of the leading item off the rest, `offset` is 0x80 for strings and 0xc0 for lists
+fn rlp_item(data: &[u8], offset: u8) -> Option<(&[u8], &[u8])> {
+    let (&prefix, rest) = data.split_first()?;
+    if offset == 0x80 && prefix < 0x80 {
+        return Some(data.split_at(1));
+    }
+    let short = prefix.checked_sub(offset).filter(|&short| short < 0x40)?;
+    let (len, rest) = if short <= 55 {
+        (short as usize, rest)
+    } else {
+        let len_of_len = (short - 55) as usize;
+        if rest.len() < len_of_len {
+            return None;
+        }
+        let (len, rest) = rest.split_at(len_of_len);
+        (usize::try_from(be_u64(len)?).ok()?, rest)
+    };
+    (rest.len() >= len).then(|| rest.split_at(len))
+}
+
+fn be_u64(bytes: &[u8]) -> Option<u64> {
+    (bytes.len() <= 8).then(|| {
+        bytes
+            .iter()
+            .fold(0, |value, &byte| (value << 8) | byte as u64)
+    })
+}
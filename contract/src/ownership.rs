@@ -0,0 +1,152 @@
+use near_sdk::serde_json::json;
+use near_sdk::{env, near, AccountId};
+
+use crate::constants::OWNER_PROPOSAL_TTL_MS;
+use crate::errors::{ensure, ContractError, OrContractError};
+use crate::events::emit_event;
+use crate::types::OwnerProposal;
+use crate::{Contract, ContractExt};
+
+#[near]
+impl Contract {
+    // @dev the proposed account has to accept within OWNER_PROPOSAL_TTL_MS after the timelock ends
+    pub fn propose_owner(&mut self, new_owner: AccountId, timelock_ms: Option<u64>) {
+        self.require_owner();
+        ensure!(new_owner != self.owner_id, ContractError::AlreadyOwner);
+
+        let now = env::block_timestamp_ms();
+        let accept_after = now + timelock_ms.unwrap_or(0);
+        self.pending_owner = Some(OwnerProposal {
+            account_id: new_owner.clone(),
+            proposed_at: now,
+            accept_after,
+            expires_at: accept_after + OWNER_PROPOSAL_TTL_MS,
+        });
+
+        emit_event(
+            "owner_proposed",
+            json!({ "owner_id": self.owner_id, "new_owner": new_owner, "accept_after": accept_after }),
+        );
+    }
+
+    pub fn accept_owner(&mut self) {
+        let proposal = self
+            .pending_owner
+            .clone()
+            .or_error(ContractError::NoPendingOwnerProposal);
+        let now = env::block_timestamp_ms();
+
+        ensure!(
+            env::predecessor_account_id() == proposal.account_id,
+            ContractError::OnlyProposedOwner
+        );
+        ensure!(
+            now >= proposal.accept_after,
+            ContractError::OwnerProposalTimelocked
+        );
+        ensure!(
+            now <= proposal.expires_at,
+            ContractError::OwnerProposalExpired
+        );
+
+        let previous_owner = std::mem::replace(&mut self.owner_id, proposal.account_id);
+        self.pending_owner = None;
+
+        emit_event(
+            "owner_changed",
+            json!({ "previous_owner": previous_owner, "owner_id": self.owner_id }),
+        );
+    }
+
+    pub fn cancel_owner_proposal(&mut self) {
+        self.require_owner();
+        ensure!(
+            self.pending_owner.is_some(),
+            ContractError::NoPendingOwnerProposal
+        );
+        self.pending_owner = None;
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    pub fn get_pending_owner(&self) -> Option<OwnerProposal> {
+        self.pending_owner.clone()
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::constants::OWNER_PROPOSAL_TTL_MS;
+    use crate::test_helpers::*;
+
+    const NEW_OWNER: &str = "dao.testnet";
+    const TIMELOCK_MS: u64 = 1_000;
+
+    fn propose(timelock_ms: Option<u64>) -> crate::Contract {
+        set_context_with_timestamp(OWNER, 1_000);
+
+        let mut contract = init_contract_with_defaults();
+        contract.propose_owner(NEW_OWNER.parse().unwrap(), timelock_ms);
+        contract
+    }
+
+    #[test]
+    fn test_transfer_ownership() {
+        let mut contract = propose(None);
+
+        set_context_with_timestamp(NEW_OWNER, 1_000);
+        contract.accept_owner();
+
+        assert_eq!(contract.get_owner().as_str(), NEW_OWNER);
+        assert!(contract.get_pending_owner().is_none());
+    }
+
+    #[test]
+    fn test_accept_after_timelock() {
+        let mut contract = propose(Some(TIMELOCK_MS));
+
+        set_context_with_timestamp(NEW_OWNER, 1_000 + TIMELOCK_MS);
+        contract.accept_owner();
+
+        assert_eq!(contract.get_owner().as_str(), NEW_OWNER);
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner proposal is timelocked")]
+    fn fails_to_accept_during_timelock() {
+        let mut contract = propose(Some(TIMELOCK_MS));
+
+        set_context_with_timestamp(NEW_OWNER, 1_000 + TIMELOCK_MS - 1);
+        contract.accept_owner();
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner proposal expired")]
+    fn fails_to_accept_expired_proposal() {
+        let mut contract = propose(None);
+
+        set_context_with_timestamp(NEW_OWNER, 1_000 + OWNER_PROPOSAL_TTL_MS + 1);
+        contract.accept_owner();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept")]
+    fn fails_if_other_account_accepts() {
+        let mut contract = propose(None);
+
+        set_context_with_timestamp("random.near", 1_000);
+        contract.accept_owner();
+    }
+
+    #[test]
+    #[should_panic(expected = "No pending owner proposal")]
+    fn fails_to_accept_cancelled_proposal() {
+        let mut contract = propose(None);
+        contract.cancel_owner_proposal();
+
+        set_context_with_timestamp(NEW_OWNER, 1_000);
+        contract.accept_owner();
+    }
+}
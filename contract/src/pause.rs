@@ -0,0 +1,196 @@
+use near_sdk::serde_json::json;
+use near_sdk::{env, near};
+
+use crate::errors::{ensure, ContractError};
+use crate::events::emit_event;
+use crate::types::{ChainId, Flow, PauseStatus, Role};
+use crate::{Contract, ContractExt};
+
+#[near]
+impl Contract {
+    pub fn pause(&mut self) {
+        self.require_role(Role::Guardian);
+        self.paused = true;
+        emit_event("paused", json!({ "by": env::predecessor_account_id() }));
+    }
+
+    pub fn unpause(&mut self) {
+        self.require_owner();
+        self.paused = false;
+        emit_event("unpaused", json!({ "by": env::predecessor_account_id() }));
+    }
+
+    pub fn pause_chain(&mut self, chain_id: ChainId) {
+        self.require_role(Role::Guardian);
+        self.paused_chains.insert(chain_id);
+        emit_event(
+            "chain_paused",
+            json!({ "by": env::predecessor_account_id(), "chain_id": chain_id }),
+        );
+    }
+
+    pub fn unpause_chain(&mut self, chain_id: ChainId) {
+        self.require_owner();
+        self.paused_chains.remove(&chain_id);
+        emit_event(
+            "chain_unpaused",
+            json!({ "by": env::predecessor_account_id(), "chain_id": chain_id }),
+        );
+    }
+
+    pub fn pause_flow(&mut self, flow: Flow) {
+        self.require_role(Role::Guardian);
+        if !self.paused_flows.contains(&flow) {
+            self.paused_flows.push(flow.clone());
+        }
+        emit_event(
+            "flow_paused",
+            json!({ "by": env::predecessor_account_id(), "flow": flow }),
+        );
+    }
+
+    pub fn unpause_flow(&mut self, flow: Flow) {
+        self.require_owner();
+        self.paused_flows.retain(|f| *f != flow);
+        emit_event(
+            "flow_unpaused",
+            json!({ "by": env::predecessor_account_id(), "flow": flow }),
+        );
+    }
+
+    pub fn get_pause_status(&self) -> PauseStatus {
+        PauseStatus {
+            paused: self.paused,
+            paused_chains: self.paused_chains.iter().copied().collect(),
+            paused_flows: self.paused_flows.clone(),
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_paused(&self) {
+        ensure!(!self.paused, ContractError::ContractPaused);
+    }
+
+    pub(crate) fn assert_chain_not_paused(&self, chain_id: ChainId) {
+        self.assert_not_paused();
+        ensure!(
+            !self.paused_chains.contains(&chain_id),
+            ContractError::ChainPaused(chain_id)
+        );
+    }
+
+    pub(crate) fn assert_flow_not_paused(&self, flow: &Flow) {
+        self.assert_not_paused();
+        ensure!(
+            !self.paused_flows.contains(flow),
+            ContractError::FlowPaused(flow.clone())
+        );
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::test_utils::get_logs;
+
+    const GUARDIAN: &str = "guardian.testnet";
+
+    fn init_contract_with_guardian() -> crate::Contract {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_worker();
+        contract.grant_role(GUARDIAN.parse().unwrap(), Role::Guardian);
+        contract
+    }
+
+    fn start_rebalance(contract: &mut crate::Contract) {
+        set_context(WORKER);
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+    }
+
+    #[test]
+    fn test_pause_emits_event() {
+        let mut contract = init_contract_with_guardian();
+
+        set_context(GUARDIAN);
+        contract.pause();
+
+        assert!(contract.get_pause_status().paused);
+        assert!(get_logs()
+            .iter()
+            .any(|log| log.starts_with("EVENT_JSON:") && log.contains("\"event\":\"paused\"")));
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn fails_to_start_rebalance_when_paused() {
+        let mut contract = init_contract_with_guardian();
+
+        set_context(GUARDIAN);
+        contract.pause();
+
+        start_rebalance(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain 2 is paused")]
+    fn fails_to_start_rebalance_when_chain_is_paused() {
+        let mut contract = init_contract_with_guardian();
+
+        set_context(GUARDIAN);
+        contract.pause_chain(DEFAULT_DESTINATION_CHAIN);
+
+        start_rebalance(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "Flow AaveToAave is paused")]
+    fn fails_to_sign_when_flow_is_paused() {
+        let mut contract = init_contract_with_guardian();
+        start_rebalance(&mut contract);
+
+        set_context(GUARDIAN);
+        contract.pause_flow(Flow::AaveToAave);
+
+        contract.trigger_signature(Step::AaveWithdraw, build_partial_tx(), 10);
+    }
+
+    #[test]
+    fn test_unpause_restores_signing() {
+        let mut contract = init_contract_with_guardian();
+
+        set_context(GUARDIAN);
+        contract.pause();
+        contract.pause_chain(DEFAULT_SOURCE_CHAIN);
+        contract.pause_flow(Flow::AaveToAave);
+
+        set_context(OWNER);
+        contract.unpause();
+        contract.unpause_chain(DEFAULT_SOURCE_CHAIN);
+        contract.unpause_flow(Flow::AaveToAave);
+
+        let status = contract.get_pause_status();
+        assert!(!status.paused);
+        assert!(status.paused_chains.is_empty());
+        assert!(status.paused_flows.is_empty());
+
+        start_rebalance(&mut contract);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_if_guardian_unpauses() {
+        let mut contract = init_contract_with_guardian();
+
+        set_context(GUARDIAN);
+        contract.pause();
+        contract.unpause();
+    }
+}
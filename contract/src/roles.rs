@@ -0,0 +1,126 @@
+use near_sdk::{env, near, AccountId};
+
+use crate::types::Role;
+use crate::{Contract, ContractExt};
+
+#[near]
+impl Contract {
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        self.require_owner();
+
+        let mut roles = self.get_roles(account_id.clone());
+        if !roles.contains(&role) {
+            roles.push(role);
+            self.roles_by_account_id.insert(account_id.clone(), roles);
+        }
+
+        env::log_str(&format!("Role {:?} granted to {}", role, account_id));
+    }
+
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        self.require_owner();
+
+        if let Some(roles) = self.roles_by_account_id.get_mut(&account_id) {
+            roles.retain(|r| *r != role);
+            if roles.is_empty() {
+                self.roles_by_account_id.remove(&account_id);
+            }
+        }
+
+        env::log_str(&format!("Role {:?} revoked from {}", role, account_id));
+    }
+
+    pub fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+        self.roles_by_account_id
+            .get(&account_id)
+            .is_some_and(|roles| roles.contains(&role))
+    }
+
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        self.roles_by_account_id
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.roles_by_account_id
+            .iter()
+            .filter(|(_, roles)| roles.contains(&role))
+            .map(|(account_id, _)| account_id.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::AccountId;
+
+    const OPERATOR: &str = "operator.testnet";
+
+    fn operator() -> AccountId {
+        OPERATOR.parse().unwrap()
+    }
+
+    #[test]
+    fn test_grant_and_revoke_role() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.grant_role(operator(), Role::Operator);
+        contract.grant_role(operator(), Role::Operator);
+        contract.grant_role(operator(), Role::Guardian);
+
+        assert!(contract.has_role(operator(), Role::Operator));
+        assert_eq!(
+            contract.get_roles(operator()),
+            vec![Role::Operator, Role::Guardian]
+        );
+        assert_eq!(contract.get_role_members(Role::Operator), vec![operator()]);
+
+        contract.revoke_role(operator(), Role::Operator);
+        assert!(!contract.has_role(operator(), Role::Operator));
+        assert_eq!(contract.get_roles(operator()), vec![Role::Guardian]);
+
+        contract.revoke_role(operator(), Role::Guardian);
+        assert!(contract.get_role_members(Role::Guardian).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_to_grant_if_not_owner() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+
+        set_context(OPERATOR);
+        contract.grant_role(operator(), Role::Operator);
+    }
+
+    #[test]
+    fn test_role_member_can_call_privileged_method() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.grant_role(operator(), Role::ConfigManager);
+
+        set_context(OPERATOR);
+        contract.set_session_timeout(1_000);
+
+        assert_eq!(contract.get_session_timeout(), 1_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing role ConfigManager")]
+    fn fails_if_role_is_missing() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.grant_role(operator(), Role::Operator);
+
+        set_context(OPERATOR);
+        contract.set_session_timeout(1_000);
+    }
+}
@@ -1,17 +1,22 @@
+use crate::errors::{ContractError, OrContractError};
 use crate::{
-    constants::*, ecdsa, encoders, external::this_contract, types::SnapshotDigestArgs, Contract,
-    ContractExt,
+    ecdsa, encoders,
+    external::this_contract,
+    types::{SignatureDeposit, SnapshotDigestArgs},
+    Contract, ContractExt,
 };
 use near_sdk::{env, near, Gas, Promise};
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_crosschain_balance_snapshot_tx(
         &self,
         args: SnapshotDigestArgs,
         callback_gas_tgas: u64,
     ) -> Promise {
         self.assert_agent_is_calling();
+        self.assert_chain_not_paused(args.chain_id);
 
         let digest = encoders::rebalancer::vault::compute_snapshot_digest(
             args.chain_id,
@@ -23,12 +28,13 @@ impl Contract {
             args.receiver,
         );
 
-        let payload_hash = digest.try_into().expect("Payload must be 32 bytes long");
+        let payload_hash = digest.try_into().or_error(ContractError::InvalidPayload);
+        let deposit = SignatureDeposit::collect(self.signer.signature_deposit);
 
-        ecdsa::get_sig(payload_hash, PATH.to_string(), KEY_VERSION).then(
+        ecdsa::get_sig(payload_hash, &self.signer).then(
             this_contract::ext(env::current_account_id())
                 .with_static_gas(Gas::from_tgas(callback_gas_tgas))
-                .sign_crosschain_balance_callback(),
+                .sign_crosschain_balance_callback(payload_hash, deposit),
         )
     }
 }
@@ -53,4 +59,3 @@ mod maintests {}
 // TODO: Improvements
 // Support add new configurations and new chains
 // Withdrawals
-// Emergency stop
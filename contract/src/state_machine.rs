@@ -1,45 +1,66 @@
-use near_sdk::{env, require};
+use near_sdk::env;
 use omni_transaction::evm::EVMTransaction;
 
+use crate::errors::{ensure, ContractError, OrContractError};
 use crate::{
-    types::{CacheKey, ChainId, Config, Flow, PayloadType, Step},
+    types::{
+        ActiveSession, ActivityStatus, CacheKey, ChainId, Config, ExecutionStatus, Flow,
+        PayloadType, Step, StepRecord,
+    },
     Contract,
 };
+use near_sdk::FunctionError;
 
 impl Contract {
     pub(crate) fn hash_payload(&self, ethereum_tx: &EVMTransaction) -> [u8; 32] {
         env::keccak256(&ethereum_tx.build_for_signing())
             .try_into()
-            .expect("Payload must be 32 bytes long")
+            .or_error(ContractError::InvalidPayload)
     }
 
-    fn get_chain_id_from_the_step_and_current_session(&self, step: Step) -> ChainId {
+    pub(crate) fn get_chain_id_from_the_step_and_current_session(&self, step: Step) -> ChainId {
         let log = self.get_activity_log();
-        let flow = self.get_active_session().flow.clone();
+        let session = self.get_active_session();
+        let flow = session.flow.clone();
+
+        // @dev unwinding never leaves the source chain
+        if session.unwinding {
+            ensure!(
+                self.session_sequence(session).contains(&step),
+                ContractError::InvalidFlowStep
+            );
+            return log.source_chain;
+        }
 
         match (flow, step) {
             // -------- Aave -> Aave --------
             (Flow::AaveToAave, PayloadType::AaveWithdraw)
+            | (Flow::AaveToAave, PayloadType::CCTPApproveBurn)
             | (Flow::AaveToAave, PayloadType::CCTPBurn) => log.source_chain,
 
             (Flow::AaveToAave, PayloadType::CCTPMint)
+            | (Flow::AaveToAave, PayloadType::AaveApproveSupply)
             | (Flow::AaveToAave, PayloadType::AaveSupply) => log.destination_chain,
 
             // -------- Rebalancer -> Aave --------
             (Flow::RebalancerToAave, PayloadType::RebalancerWithdrawToAllocate)
+            | (Flow::RebalancerToAave, PayloadType::CCTPApproveBurn)
             | (Flow::RebalancerToAave, PayloadType::CCTPBurn) => log.source_chain,
 
             (Flow::RebalancerToAave, PayloadType::CCTPMint)
+            | (Flow::RebalancerToAave, PayloadType::AaveApproveSupply)
             | (Flow::RebalancerToAave, PayloadType::AaveSupply) => log.destination_chain,
 
             // -------- Aave -> Rebalancer --------
             (Flow::AaveToRebalancer, PayloadType::AaveWithdraw)
+            | (Flow::AaveToRebalancer, PayloadType::CCTPApproveBurn)
             | (Flow::AaveToRebalancer, PayloadType::CCTPBurn) => log.source_chain,
 
             (Flow::AaveToRebalancer, PayloadType::CCTPMint)
+            | (Flow::AaveToRebalancer, PayloadType::RebalancerApproveDeposit)
             | (Flow::AaveToRebalancer, PayloadType::RebalancerDeposit) => log.destination_chain,
 
-            _ => env::panic_str("Invalid (flow, step) combination for chain selection"),
+            _ => ContractError::InvalidFlowStep.panic(),
         }
     }
 
@@ -48,31 +69,239 @@ impl Contract {
         self.get_chain_config(&chain_id)
     }
 
+    // @dev only input and to are overwritten by the builders, the rest comes from the caller
+    pub(crate) fn assert_valid_partial_transaction(&self, tx: &EVMTransaction, chain_id: ChainId) {
+        ensure!(tx.chain_id == chain_id, ContractError::ChainIdMismatch);
+        ensure!(tx.value == 0, ContractError::NonZeroValue);
+        ensure!(
+            tx.access_list.is_empty(),
+            ContractError::UnexpectedAccessList
+        );
+    }
+
+    // @dev the first nonce seen on a chain seeds the tracking, use set_next_nonce to resync
+    pub(crate) fn reserve_evm_nonce(&mut self, chain_id: ChainId, nonce: u64, replacement: bool) {
+        let next_nonce = self.next_evm_nonce_by_chain.get(&chain_id).copied();
+
+        if replacement {
+            ensure!(
+                next_nonce.is_some_and(|next| nonce < next),
+                ContractError::ReplacedNonceNeverUsed
+            );
+            return;
+        }
+
+        if let Some(next) = next_nonce {
+            ensure!(nonce >= next, ContractError::NonceAlreadyUsed);
+            ensure!(nonce == next, ContractError::NonceGap);
+        }
+
+        let next = nonce.checked_add(1).or_error(ContractError::NonceOverflow);
+        self.next_evm_nonce_by_chain.insert(chain_id, next);
+    }
+
+    // @dev hands the nonce back when the signature failed and nothing was reserved after it,
+    // never panics so callbacks can always roll back
+    pub(crate) fn release_evm_nonce(&mut self, chain_id: ChainId, nonce: u64) {
+        let reserved = nonce
+            .checked_add(1)
+            .is_some_and(|next| self.next_evm_nonce_by_chain.get(&chain_id) == Some(&next));
+        if reserved {
+            self.next_evm_nonce_by_chain.insert(chain_id, nonce);
+        }
+    }
+
     pub(crate) fn is_chain_supported(&self, chain_id: &ChainId) {
-        require!(
+        ensure!(
             self.supported_chains.contains(chain_id),
-            "Chain not supported"
+            ContractError::ChainNotSupported
         );
     }
 
+    pub(crate) fn assert_chain_enabled(&self, chain_id: &ChainId) {
+        self.is_chain_supported(chain_id);
+        ensure!(
+            !self.disabled_chains.contains(chain_id),
+            ContractError::ChainDisabled
+        );
+    }
+
+    // @dev the vault only lives on `self.source_chain`, and Aave there is reached through it
+    pub(crate) fn assert_flow_matches_chains(
+        &self,
+        flow: &Flow,
+        source_chain: ChainId,
+        destination_chain: ChainId,
+    ) {
+        ensure!(
+            source_chain != destination_chain,
+            ContractError::SameSourceAndDestination
+        );
+        let valid = match flow {
+            Flow::AaveToAave => destination_chain != self.source_chain,
+            Flow::RebalancerToAave => source_chain == self.source_chain,
+            Flow::AaveToRebalancer => destination_chain == self.source_chain,
+        };
+        ensure!(valid, ContractError::FlowNotValidForChains(flow.clone()));
+    }
+
+    pub(crate) fn assert_chain_not_in_active_session(&self, chain_id: ChainId) {
+        if let Some(session) = &self.active_session {
+            let log = self
+                .logs
+                .get(&session.nonce)
+                .or_error(ContractError::LogNotFound);
+            ensure!(
+                log.source_chain != chain_id && log.destination_chain != chain_id,
+                ContractError::ChainUsedByActiveSession
+            );
+        }
+    }
+
     pub fn has_signature(&self, step: Step) -> bool {
         let nonce = self.get_active_session().nonce;
         self.signatures_by_nonce_and_type
             .contains_key(&CacheKey::new(nonce, step as u8))
     }
 
+    pub(crate) fn session_sequence(&self, session: &ActiveSession) -> &'static [Step] {
+        if session.unwinding {
+            let source_chain = self
+                .logs
+                .get(&session.nonce)
+                .or_error(ContractError::LogNotFound)
+                .source_chain;
+            session
+                .flow
+                .unwind_sequence(source_chain == self.source_chain)
+        } else {
+            session.flow.sequence()
+        }
+    }
+
+    pub(crate) fn unsigned_steps(&self) -> Vec<Step> {
+        self.session_sequence(self.get_active_session())
+            .iter()
+            .copied()
+            .filter(|&st| !self.has_signature(st))
+            .collect()
+    }
+
     pub(crate) fn assert_step_is_next(&self, requested: Step) {
-        for &st in self.get_active_session().flow.sequence() {
+        if let Err(error) = self.check_step_is_next(requested) {
+            error.panic();
+        }
+    }
+
+    pub(crate) fn check_step_is_next(&self, requested: Step) -> Result<(), ContractError> {
+        let mut previous: Option<Step> = None;
+        for &st in self.session_sequence(self.get_active_session()) {
             if !self.has_signature(st) {
-                require!(st == requested, "Wrong step for current position");
-                return;
+                if st != requested {
+                    return Err(ContractError::WrongStep);
+                }
+                if self.require_step_confirmations
+                    && previous.is_some_and(|previous| !self.is_step_confirmed(previous))
+                {
+                    return Err(ContractError::PreviousStepNotConfirmed);
+                }
+                return Ok(());
             }
+            previous = Some(st);
+        }
+        Err(ContractError::FlowAlreadyFinished)
+    }
+
+    // @dev the session may have been aborted, expired or moved on while the MPC call was in flight
+    pub(crate) fn check_callback_session(&self, nonce: u64) -> Result<(), ContractError> {
+        match &self.active_session {
+            Some(session) if session.nonce == nonce => Ok(()),
+            Some(_) => Err(ContractError::CallbackNonceMismatch),
+            None => Err(ContractError::NoActiveSession),
+        }
+    }
+
+    pub(crate) fn get_step_record(&self, step: Step) -> Option<&StepRecord> {
+        let nonce = self.get_active_session().nonce;
+        self.logs
+            .get(&nonce)
+            .or_error(ContractError::LogNotFound)
+            .steps
+            .iter()
+            .find(|record| record.step == step)
+    }
+
+    pub(crate) fn is_step_confirmed(&self, step: Step) -> bool {
+        self.get_step_record(step)
+            .and_then(|record| record.confirmation.as_ref())
+            .is_some_and(|confirmation| confirmation.status == ExecutionStatus::Success)
+    }
+
+    pub(crate) fn assert_step_amount(&self, step: Step, amount: u128) {
+        let session = self.get_active_session();
+        let session_amount = self.get_activity_log().amount;
+
+        if !session.unwinding && session.flow.is_after_burn(step) {
+            // @dev CCTP may keep up to max_fee of the burned amount
+            let max_fee = session.cctp_max_fee.unwrap_or(0);
+            ensure!(
+                amount <= session_amount && amount >= session_amount - max_fee,
+                ContractError::AmountOutOfBounds
+            );
+        } else {
+            ensure!(
+                amount == session_amount,
+                ContractError::AmountDoesNotMatchSession
+            );
+        }
+    }
+
+    pub(crate) fn record_cctp_max_fee(&mut self, max_fee: u128) {
+        ensure!(
+            max_fee < self.get_activity_log().amount,
+            ContractError::MaxFeeTooHigh
+        );
+
+        if !self.has_signature(Step::CCTPBurn) {
+            self.active_session.as_mut().unwrap().cctp_max_fee = Some(max_fee);
+        }
+    }
+
+    pub(crate) fn is_session_expired(&self, session: &ActiveSession) -> bool {
+        env::block_timestamp_ms() > session.started_at.saturating_add(self.session_timeout_ms)
+    }
+
+    pub(crate) fn assert_session_not_expired(&self) {
+        ensure!(
+            !self.is_session_expired(self.get_active_session()),
+            ContractError::SessionExpired
+        );
+    }
+
+    // @dev once the burn is signed the USDC may be in flight, only abort_rebalance or
+    // recover_session can close that session
+    pub(crate) fn clear_expired_session(&mut self) {
+        let expired = self
+            .active_session
+            .as_ref()
+            .is_some_and(|session| self.is_session_expired(session));
+
+        if expired && !self.has_signature(Step::CCTPBurn) {
+            let session = self.active_session.take().unwrap();
+            self.logs
+                .get_mut(&session.nonce)
+                .or_error(ContractError::LogNotFound)
+                .set_status(ActivityStatus::Aborted, Some("Session expired".to_string()));
+            env::log_str(&format!("Rebalance {} expired", session.nonce));
         }
-        env::panic_str("Flow already finished");
     }
 
     pub(crate) fn assert_no_active_session(&mut self) {
-        require!(self.active_session.is_none(), "Another action in progress");
+        self.clear_expired_session();
+        ensure!(
+            self.active_session.is_none(),
+            ContractError::AnotherActionInProgress
+        );
     }
 }
 
@@ -81,22 +310,60 @@ impl Flow {
         match self {
             Flow::AaveToAave => &[
                 PayloadType::AaveWithdraw,
+                PayloadType::CCTPApproveBurn,
                 PayloadType::CCTPBurn,
                 PayloadType::CCTPMint,
+                PayloadType::AaveApproveSupply,
                 PayloadType::AaveSupply,
             ],
             Flow::RebalancerToAave => &[
                 PayloadType::RebalancerWithdrawToAllocate,
+                PayloadType::CCTPApproveBurn,
                 PayloadType::CCTPBurn,
                 PayloadType::CCTPMint,
+                PayloadType::AaveApproveSupply,
                 PayloadType::AaveSupply,
             ],
             Flow::AaveToRebalancer => &[
                 PayloadType::AaveWithdraw,
+                PayloadType::CCTPApproveBurn,
                 PayloadType::CCTPBurn,
                 PayloadType::CCTPMint,
+                PayloadType::RebalancerApproveDeposit,
+                PayloadType::RebalancerDeposit,
+            ],
+        }
+    }
+
+    pub fn is_after_burn(&self, step: PayloadType) -> bool {
+        let sequence = self.sequence();
+        let position = |target: PayloadType| sequence.iter().position(|&st| st == target);
+
+        matches!(
+            (position(step), position(PayloadType::CCTPBurn)),
+            (Some(step_position), Some(burn_position)) if step_position > burn_position
+        )
+    }
+
+    // @dev puts the funds back on the source chain, only valid before the burn is signed. Aave on the
+    // vault chain is only reached through the vault, so what was withdrawn there goes into the vault
+    pub fn unwind_sequence(&self, on_vault_chain: bool) -> &'static [PayloadType] {
+        match (self, on_vault_chain) {
+            (Flow::RebalancerToAave, _) => &[
+                PayloadType::RebalancerWithdrawToAllocate,
+                PayloadType::RebalancerApproveDeposit,
                 PayloadType::RebalancerDeposit,
             ],
+            (Flow::AaveToAave | Flow::AaveToRebalancer, true) => &[
+                PayloadType::AaveWithdraw,
+                PayloadType::RebalancerApproveDeposit,
+                PayloadType::RebalancerDeposit,
+            ],
+            (Flow::AaveToAave | Flow::AaveToRebalancer, false) => &[
+                PayloadType::AaveWithdraw,
+                PayloadType::AaveApproveSupply,
+                PayloadType::AaveSupply,
+            ],
         }
     }
 }
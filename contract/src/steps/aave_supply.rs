@@ -1,34 +1,36 @@
-use std::str::FromStr;
-
 use crate::{
     tx_builders,
     types::{AaveArgs, Step},
     Contract, ContractExt,
 };
-use alloy_primitives::Address;
-use near_sdk::{near, Promise};
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_aave_supply_tx(
         &mut self,
         args: AaveArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
-        self.assert_step_is_next(Step::AaveSupply);
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_aave_supply_tx(args);
+
+        self.trigger_signature(Step::AaveSupply, tx, callback_gas_tgas)
+    }
+}
+
+impl Contract {
+    pub(crate) fn prepare_aave_supply_tx(&self, args: AaveArgs) -> EVMTransaction {
+        self.assert_step_amount(Step::AaveSupply, args.amount);
 
         let cfg = self.get_chain_config_from_step_and_current_session(Step::AaveSupply);
 
         let mut tx = args.clone().partial_transaction;
         tx.input = tx_builders::build_aave_supply_tx(args, cfg.aave.clone());
-        tx.to = Some(
-            Address::from_str(&cfg.aave.lending_pool_address)
-                .expect("Invalid lending pool")
-                .into_array(),
-        );
-
-        self.trigger_signature(Step::AaveSupply, tx, callback_gas_tgas)
+        tx.to = Some(cfg.aave.lending_pool_address.into_array());
+        tx
     }
 }
 
@@ -40,4 +42,60 @@ mod maintests {
 
     #[test]
     fn test_() {}
+
+    const MAX_FEE: u128 = 1_000;
+
+    fn start_session_after_mint() -> crate::Contract {
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[
+                Step::AaveWithdraw,
+                Step::CCTPApproveBurn,
+                Step::CCTPBurn,
+                Step::CCTPMint,
+                Step::AaveApproveSupply,
+            ],
+        );
+        contract.active_session.as_mut().unwrap().cctp_max_fee = Some(MAX_FEE);
+        contract
+    }
+
+    fn build_args(amount: u128) -> AaveArgs {
+        let mut partial_transaction = build_partial_tx();
+        partial_transaction.chain_id = DEFAULT_DESTINATION_CHAIN;
+
+        AaveArgs {
+            amount,
+            partial_transaction,
+        }
+    }
+
+    #[test]
+    fn test_accepts_amount_minus_max_fee() {
+        set_context(WORKER);
+
+        let mut contract = start_session_after_mint();
+
+        contract.build_and_sign_aave_supply_tx(build_args(DEFAULT_AMOUNT - MAX_FEE), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount out of bounds for session")]
+    fn fails_if_amount_below_max_fee_bound() {
+        set_context(WORKER);
+
+        let mut contract = start_session_after_mint();
+
+        contract.build_and_sign_aave_supply_tx(build_args(DEFAULT_AMOUNT - MAX_FEE - 1), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount out of bounds for session")]
+    fn fails_if_amount_above_session_amount() {
+        set_context(WORKER);
+
+        let mut contract = start_session_after_mint();
+
+        contract.build_and_sign_aave_supply_tx(build_args(DEFAULT_AMOUNT + 1), 10);
+    }
 }
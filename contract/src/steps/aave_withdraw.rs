@@ -1,32 +1,36 @@
-use std::str::FromStr;
-
 use crate::{
     tx_builders,
     types::{AaveArgs, Step},
     Contract, ContractExt,
 };
-use alloy_primitives::Address;
-use near_sdk::{near, Promise};
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_aave_withdraw_tx(
         &mut self,
         args: AaveArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_aave_withdraw_tx(args);
+
+        self.trigger_signature(Step::AaveWithdraw, tx, callback_gas_tgas)
+    }
+}
+
+impl Contract {
+    pub(crate) fn prepare_aave_withdraw_tx(&self, args: AaveArgs) -> EVMTransaction {
+        self.assert_step_amount(Step::AaveWithdraw, args.amount);
+
         let cfg = self.get_chain_config_from_step_and_current_session(Step::AaveWithdraw);
 
         let mut tx = args.clone().partial_transaction;
         tx.input = tx_builders::build_aave_withdraw_tx(args, cfg.aave.clone());
-        tx.to = Some(
-            Address::from_str(&cfg.aave.lending_pool_address)
-                .expect("Invalid lending pool")
-                .into_array(),
-        );
-
-        self.trigger_signature(Step::AaveWithdraw, tx, callback_gas_tgas)
+        tx.to = Some(cfg.aave.lending_pool_address.into_array());
+        tx
     }
 }
 
@@ -0,0 +1,102 @@
+use crate::errors::{ensure, ContractError, OrContractError};
+use crate::{
+    types::{ActivityStatus, Role},
+    Contract, ContractExt,
+};
+use near_sdk::{env, near};
+
+#[near]
+impl Contract {
+    pub fn abort_rebalance(&mut self, reason: String) -> u64 {
+        self.require_role(Role::Operator);
+        ensure!(
+            self.active_session.is_some(),
+            ContractError::NoActiveSession
+        );
+
+        let session = self.active_session.take().unwrap();
+        self.logs
+            .get_mut(&session.nonce)
+            .or_error(ContractError::LogNotFound)
+            .set_status(ActivityStatus::Aborted, Some(reason.clone()));
+
+        env::log_str(&format!("Rebalance {} aborted: {}", session.nonce, reason));
+
+        session.nonce
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+
+    #[test]
+    fn test_abort_rebalance() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let nonce = contract.start_rebalance(
+            Flow::RebalancerToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            1_000,
+        );
+
+        set_context(OWNER);
+        let reason = "CCTP attestation service down".to_string();
+        let aborted = contract.abort_rebalance(reason.clone());
+
+        assert_eq!(aborted, nonce);
+        assert!(contract.active_session.is_none());
+
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.status, ActivityStatus::Aborted);
+        assert_eq!(log.failure_reason, Some(reason));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_if_not_owner() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        contract.start_rebalance(
+            Flow::RebalancerToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            1_000,
+        );
+
+        set_context("random.near");
+        contract.abort_rebalance("not allowed".to_string());
+    }
+
+    #[test]
+    fn test_operator_can_abort() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let nonce = contract.start_rebalance(
+            Flow::RebalancerToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            1_000,
+        );
+
+        set_context(OWNER);
+        contract.grant_role("operator.testnet".parse().unwrap(), Role::Operator);
+
+        set_context("operator.testnet");
+        assert_eq!(contract.abort_rebalance("ops".to_string()), nonce);
+    }
+
+    #[test]
+    #[should_panic(expected = "E200: No active session")]
+    fn fails_if_no_active_session() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.abort_rebalance("nothing to abort".to_string());
+    }
+}
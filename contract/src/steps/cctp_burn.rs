@@ -1,27 +1,33 @@
 use crate::{tx_builders, types::*, Contract, ContractExt};
-use alloy_primitives::Address;
-use near_sdk::{near, Promise};
-use std::str::FromStr;
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_cctp_burn_tx(
         &mut self,
         args: CCTPBurnArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_cctp_burn_tx(args);
+
+        self.trigger_signature(Step::CCTPBurn, tx, callback_gas_tgas)
+    }
+}
+
+impl Contract {
+    pub(crate) fn prepare_cctp_burn_tx(&mut self, args: CCTPBurnArgs) -> EVMTransaction {
+        self.assert_step_amount(Step::CCTPBurn, args.amount);
+        self.record_cctp_max_fee(args.max_fee);
+
         let cfg = self.get_chain_config_from_step_and_current_session(Step::CCTPBurn);
 
         let mut tx = args.clone().partial_burn_transaction;
         tx.input = tx_builders::build_cctp_burn_tx(args);
-        tx.to = Some(
-            Address::from_str(&cfg.cctp.messenger_address)
-                .expect("Invalid messenger")
-                .into_array(),
-        );
-
-        self.trigger_signature(Step::CCTPBurn, tx, callback_gas_tgas)
+        tx.to = Some(cfg.cctp.messenger_address.into_array());
+        tx
     }
 }
 
@@ -33,4 +39,62 @@ mod maintests {
 
     #[test]
     fn test_build_and_sign_cctp_burn_tx() {}
+
+    const MAX_FEE: u128 = 1_000;
+
+    fn build_args(amount: u128) -> CCTPBurnArgs {
+        CCTPBurnArgs {
+            amount,
+            destination_domain: 3,
+            mint_recipient: format!("{:0>64}", "20f2747bbc52453ac0774b5b2fe0e28dc6637f30"),
+            burn_token: "0x75faf114eafb1BDbe2F0316DF893fd58CE46AA4d".to_string(),
+            destination_caller: format!("{:0>64}", "20f2747bbc52453ac0774b5b2fe0e28dc6637f30"),
+            max_fee: MAX_FEE,
+            min_finality_threshold: 1000,
+            partial_burn_transaction: build_partial_tx(),
+        }
+    }
+
+    #[test]
+    fn test_burn_records_max_fee() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn],
+        );
+
+        contract.build_and_sign_cctp_burn_tx(build_args(DEFAULT_AMOUNT), 10);
+
+        assert_eq!(contract.get_active_session().cctp_max_fee, Some(MAX_FEE));
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount does not match session")]
+    fn fails_if_amount_exceeds_session_amount() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn],
+        );
+
+        contract.build_and_sign_cctp_burn_tx(build_args(DEFAULT_AMOUNT + 1), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Max fee must be lower than the session amount")]
+    fn fails_if_max_fee_exceeds_session_amount() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn],
+        );
+
+        let mut args = build_args(DEFAULT_AMOUNT);
+        args.max_fee = DEFAULT_AMOUNT;
+
+        contract.build_and_sign_cctp_burn_tx(args, 10);
+    }
 }
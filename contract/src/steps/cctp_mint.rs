@@ -1,32 +1,34 @@
-use std::str::FromStr;
-
 use crate::{
     tx_builders,
     types::{CCTPMintArgs, Step},
     Contract, ContractExt,
 };
-use alloy_primitives::Address;
-use near_sdk::{near, Promise};
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_cctp_mint_tx(
         &mut self,
         args: CCTPMintArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_cctp_mint_tx(args);
+
+        self.trigger_signature(Step::CCTPMint, tx, callback_gas_tgas)
+    }
+}
+
+impl Contract {
+    pub(crate) fn prepare_cctp_mint_tx(&self, args: CCTPMintArgs) -> EVMTransaction {
         let cfg = self.get_chain_config_from_step_and_current_session(Step::CCTPMint);
 
         let mut tx = args.clone().partial_mint_transaction;
         tx.input = tx_builders::build_cctp_mint_tx(args);
-        tx.to = Some(
-            Address::from_str(&cfg.cctp.transmitter_address)
-                .expect("Invalid transmitter")
-                .into_array(),
-        );
-
-        self.trigger_signature(Step::CCTPMint, tx, callback_gas_tgas)
+        tx.to = Some(cfg.cctp.transmitter_address.into_array());
+        tx
     }
 }
 
@@ -38,4 +40,58 @@ mod maintests {
 
     #[test]
     fn test_() {}
+
+    fn start_session_after_burn() -> crate::Contract {
+        let (contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn, Step::CCTPBurn],
+        );
+        contract
+    }
+
+    fn build_args() -> CCTPMintArgs {
+        let mut partial_mint_transaction = build_partial_tx();
+        partial_mint_transaction.chain_id = DEFAULT_DESTINATION_CHAIN;
+
+        CCTPMintArgs {
+            message: vec![0xde, 0xad],
+            attestation: vec![0xbe, 0xef],
+            partial_mint_transaction,
+        }
+    }
+
+    #[test]
+    fn test_accepts_transaction_for_step_chain() {
+        set_context(WORKER);
+
+        let mut contract = start_session_after_burn();
+
+        contract.build_and_sign_cctp_mint_tx(build_args(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transaction chain_id does not match step chain")]
+    fn fails_if_chain_id_is_not_step_chain() {
+        set_context(WORKER);
+
+        let mut contract = start_session_after_burn();
+
+        let mut args = build_args();
+        args.partial_mint_transaction.chain_id = DEFAULT_SOURCE_CHAIN; // @dev mint happens on the destination chain
+
+        contract.build_and_sign_cctp_mint_tx(args, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transaction value must be zero")]
+    fn fails_if_value_is_not_zero() {
+        set_context(WORKER);
+
+        let mut contract = start_session_after_burn();
+
+        let mut args = build_args();
+        args.partial_mint_transaction.value = 1;
+
+        contract.build_and_sign_cctp_mint_tx(args, 10);
+    }
 }
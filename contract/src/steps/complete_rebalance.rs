@@ -1,20 +1,65 @@
-use crate::{Contract, ContractExt};
-use near_sdk::{near, require};
+use crate::errors::{ensure, ContractError, OrContractError};
+use crate::{
+    types::{ActivityStatus, Role},
+    Contract, ContractExt,
+};
+use near_sdk::{env, near};
 
 #[near]
 impl Contract {
     pub fn complete_rebalance(&mut self) -> u64 {
-        self.assert_agent_is_calling();
+        self.assert_session_worker_is_calling();
+        ensure!(
+            self.unsigned_steps().is_empty(),
+            ContractError::FlowHasUnsignedSteps
+        );
+        // @dev confirm_step needs the session, so its steps have to land before it is closed
+        if self.require_step_confirmations {
+            let sequence = self.session_sequence(self.get_active_session());
+            ensure!(
+                sequence.iter().all(|&step| self.is_step_confirmed(step)),
+                ContractError::FlowHasUnconfirmedSteps
+            );
+        }
 
-        let session = self.get_active_session();
+        let session = self.active_session.take().unwrap();
+        let status = if session.unwinding {
+            ActivityStatus::Unwound
+        } else {
+            ActivityStatus::Completed
+        };
         let nonce = session.nonce;
+        self.logs
+            .get_mut(&nonce)
+            .or_error(ContractError::LogNotFound)
+            .set_status(status, None);
+
+        nonce
+    }
 
-        require!(
+    pub fn complete_rebalance_with_skipped_steps(&mut self, reason: String) -> u64 {
+        self.require_role(Role::Operator);
+        ensure!(
             self.active_session.is_some(),
-            "No active session to complete"
+            ContractError::NoActiveSession
         );
 
-        self.active_session = None;
+        let skipped_steps = self.unsigned_steps();
+        ensure!(!skipped_steps.is_empty(), ContractError::NoSkippedSteps);
+
+        let nonce = self.active_session.take().unwrap().nonce;
+
+        env::log_str(&format!(
+            "Rebalance {} completed with skipped steps {:?}: {}",
+            nonce, skipped_steps, reason
+        ));
+
+        let log = self
+            .logs
+            .get_mut(&nonce)
+            .or_error(ContractError::LogNotFound);
+        log.skipped_steps = skipped_steps;
+        log.set_status(ActivityStatus::CompletedWithSkippedSteps, Some(reason));
 
         nonce
     }
@@ -24,8 +69,139 @@ impl Contract {
 mod maintests {
     use crate::test_helpers::*;
     use crate::types::*;
-    use near_sdk::env;
 
     #[test]
-    fn test_() {}
+    fn test_complete_rebalance() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) =
+            start_session_with_signed_steps(Flow::AaveToAave, Flow::AaveToAave.sequence());
+
+        assert_eq!(contract.complete_rebalance(), nonce);
+        assert!(contract.active_session.is_none());
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Completed
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Flow has unsigned steps")]
+    fn fails_if_steps_are_pending() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPBurn],
+        );
+
+        contract.complete_rebalance();
+    }
+
+    fn record_steps(contract: &mut crate::Contract, nonce: u64, status: ExecutionStatus) {
+        let log = contract.logs.get_mut(&nonce).unwrap();
+        for &step in Flow::AaveToAave.sequence() {
+            log.steps.push(StepRecord {
+                step,
+                signed_at: 0,
+                chain_id: DEFAULT_SOURCE_CHAIN,
+                evm_nonce: 0,
+                confirmation: Some(StepConfirmation {
+                    tx_hash: format!("0x{}", "ab".repeat(32)),
+                    block_number: 42,
+                    status,
+                    confirmed_at: 0,
+                }),
+            });
+        }
+    }
+
+    #[test]
+    fn test_complete_rebalance_with_confirmed_steps() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) =
+            start_session_with_signed_steps(Flow::AaveToAave, Flow::AaveToAave.sequence());
+        contract.require_step_confirmations = true;
+        record_steps(&mut contract, nonce, ExecutionStatus::Success);
+
+        assert_eq!(contract.complete_rebalance(), nonce);
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Completed
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Flow has unconfirmed steps")]
+    fn fails_if_steps_are_unconfirmed() {
+        set_context(WORKER);
+
+        let (mut contract, _) =
+            start_session_with_signed_steps(Flow::AaveToAave, Flow::AaveToAave.sequence());
+        contract.require_step_confirmations = true;
+
+        contract.complete_rebalance();
+    }
+
+    #[test]
+    #[should_panic(expected = "Flow has unconfirmed steps")]
+    fn fails_if_a_step_reverted() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) =
+            start_session_with_signed_steps(Flow::AaveToAave, Flow::AaveToAave.sequence());
+        contract.require_step_confirmations = true;
+        record_steps(&mut contract, nonce, ExecutionStatus::Reverted);
+
+        contract.complete_rebalance();
+    }
+
+    #[test]
+    fn test_complete_rebalance_with_skipped_steps() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn, Step::CCTPBurn],
+        );
+
+        set_context(OWNER);
+        let reason = "Mint executed manually".to_string();
+        contract.complete_rebalance_with_skipped_steps(reason.clone());
+
+        assert!(contract.active_session.is_none());
+
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.status, ActivityStatus::CompletedWithSkippedSteps);
+        assert_eq!(
+            log.skipped_steps,
+            vec![Step::CCTPMint, Step::AaveApproveSupply, Step::AaveSupply]
+        );
+        assert_eq!(log.failure_reason, Some(reason));
+    }
+
+    #[test]
+    #[should_panic(expected = "No skipped steps, use complete_rebalance")]
+    fn fails_to_skip_when_flow_is_finished() {
+        set_context(WORKER);
+
+        let (mut contract, _) =
+            start_session_with_signed_steps(Flow::AaveToAave, Flow::AaveToAave.sequence());
+
+        set_context(OWNER);
+        contract.complete_rebalance_with_skipped_steps("nothing skipped".to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_to_skip_if_not_owner() {
+        set_context(WORKER);
+
+        let (mut contract, _) =
+            start_session_with_signed_steps(Flow::AaveToAave, &[Step::AaveWithdraw]);
+
+        set_context("random.near");
+        contract.complete_rebalance_with_skipped_steps("not allowed".to_string());
+    }
 }
@@ -0,0 +1,219 @@
+use crate::errors::{ensure, ContractError, OrContractError};
+use crate::{
+    types::{ActivityStatus, CacheKey, ExecutionStatus, Step, StepConfirmation},
+    Contract, ContractExt,
+};
+use near_sdk::{env, near};
+
+#[near]
+impl Contract {
+    // @dev links a signed step to the EVM transaction that executed it
+    pub fn confirm_step(
+        &mut self,
+        step: Step,
+        tx_hash: String,
+        block_number: u64,
+        status: ExecutionStatus,
+    ) {
+        self.assert_session_worker_is_calling();
+        ensure!(
+            tx_hash.len() == 66 && tx_hash.starts_with("0x") && hex::decode(&tx_hash[2..]).is_ok(),
+            ContractError::InvalidTxHash
+        );
+
+        let nonce = self.get_active_session().nonce;
+        let log = self
+            .logs
+            .get_mut(&nonce)
+            .or_error(ContractError::LogNotFound);
+        let record = log
+            .steps
+            .iter_mut()
+            .find(|record| record.step == step)
+            .or_error(ContractError::StepNotSigned);
+        ensure!(
+            record.confirmation.is_none(),
+            ContractError::StepAlreadyConfirmed
+        );
+
+        record.confirmation = Some(StepConfirmation {
+            tx_hash: tx_hash.clone(),
+            block_number,
+            status,
+            confirmed_at: env::block_timestamp_ms(),
+        });
+
+        if status == ExecutionStatus::Reverted {
+            log.set_status(
+                ActivityStatus::Failed,
+                Some(format!("{:?} reverted on chain in {}", step, tx_hash)),
+            );
+
+            // @dev the reverted transaction used up its nonce, so it cannot be replaced. The step is
+            // cleared and signed again as the next step, with a fresh nonce
+            let tx_type = step as u8;
+            let (reverted, kept): (Vec<_>, Vec<_>) =
+                log.transactions.drain(..).partition(|t| t[0] == tx_type);
+            log.transactions = kept;
+            log.superseded_transactions.extend(reverted);
+
+            let key = CacheKey::new(nonce, tx_type);
+            self.signatures_by_nonce_and_type.remove(&key);
+            self.payload_hashes_by_nonce_and_type.remove(&key);
+            self.transactions_by_nonce_and_type.remove(&key);
+            self.original_fees_by_nonce_and_type.remove(&key);
+        }
+
+        env::log_str(&format!(
+            "Step {:?} of rebalance {} confirmed in {} with status {:?}",
+            step, nonce, tx_hash, status
+        ));
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::env;
+
+    fn tx_hash() -> String {
+        format!("0x{}", "ab".repeat(32))
+    }
+
+    fn start_session_with_signed_withdraw() -> (crate::Contract, u64) {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let nonce = contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+
+        set_context(&env::current_account_id().to_string());
+        let signature = build_tx_signature(&contract, &build_partial_tx());
+        contract.sign_callback(
+            Ok(signature),
+            nonce,
+            Step::AaveWithdraw as u8,
+            build_partial_tx(),
+            build_signature_deposit(),
+        );
+
+        set_context(WORKER);
+        (contract, nonce)
+    }
+
+    #[test]
+    fn test_confirm_step() {
+        let (mut contract, nonce) = start_session_with_signed_withdraw();
+
+        contract.confirm_step(Step::AaveWithdraw, tx_hash(), 42, ExecutionStatus::Success);
+
+        let log = contract.logs.get(&nonce).unwrap();
+        let confirmation = log.steps[0].confirmation.as_ref().unwrap();
+        assert_eq!(confirmation.tx_hash, tx_hash());
+        assert_eq!(confirmation.block_number, 42);
+        assert_eq!(log.status, ActivityStatus::InProgress);
+        assert!(contract.is_step_confirmed(Step::AaveWithdraw));
+    }
+
+    #[test]
+    fn test_confirm_step_reverted_marks_failure() {
+        let (mut contract, nonce) = start_session_with_signed_withdraw();
+
+        contract.confirm_step(Step::AaveWithdraw, tx_hash(), 42, ExecutionStatus::Reverted);
+
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.status, ActivityStatus::Failed);
+        assert!(!contract.is_step_confirmed(Step::AaveWithdraw));
+    }
+
+    #[test]
+    fn test_reverted_step_is_signed_again_with_a_fresh_nonce() {
+        let (mut contract, nonce) = start_session_with_signed_withdraw();
+
+        contract.confirm_step(Step::AaveWithdraw, tx_hash(), 42, ExecutionStatus::Reverted);
+
+        assert!(!contract.has_signature(Step::AaveWithdraw));
+        assert_eq!(contract.check_step_is_next(Step::AaveWithdraw), Ok(()));
+
+        let mut tx = build_partial_tx();
+        tx.nonce += 1;
+        set_context(&env::current_account_id().to_string());
+        let signature = build_tx_signature(&contract, &tx);
+        contract.sign_callback(
+            Ok(signature),
+            nonce,
+            Step::AaveWithdraw as u8,
+            tx,
+            build_signature_deposit(),
+        );
+        set_context(WORKER);
+
+        assert!(contract.has_signature(Step::AaveWithdraw));
+        let log = contract.logs.get(&nonce).unwrap();
+        assert_eq!(log.status, ActivityStatus::InProgress);
+        assert_eq!(log.transactions.len(), 1);
+        assert_eq!(log.superseded_transactions.len(), 1);
+        assert!(log.steps[0].confirmation.is_none());
+
+        contract.confirm_step(Step::AaveWithdraw, tx_hash(), 43, ExecutionStatus::Success);
+        assert!(contract.is_step_confirmed(Step::AaveWithdraw));
+    }
+
+    #[test]
+    #[should_panic(expected = "Step not signed yet")]
+    fn test_confirm_step_fails_if_not_signed() {
+        let (mut contract, _) = start_session_with_signed_withdraw();
+
+        contract.confirm_step(Step::AaveSupply, tx_hash(), 42, ExecutionStatus::Success);
+    }
+
+    #[test]
+    #[should_panic(expected = "Step already confirmed")]
+    fn test_confirm_step_fails_if_already_confirmed() {
+        let (mut contract, _) = start_session_with_signed_withdraw();
+
+        contract.confirm_step(Step::AaveWithdraw, tx_hash(), 42, ExecutionStatus::Success);
+        contract.confirm_step(Step::AaveWithdraw, tx_hash(), 43, ExecutionStatus::Success);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid tx hash")]
+    fn test_confirm_step_fails_with_invalid_hash() {
+        let (mut contract, _) = start_session_with_signed_withdraw();
+
+        contract.confirm_step(
+            Step::AaveWithdraw,
+            "0x1234".to_string(),
+            42,
+            ExecutionStatus::Success,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Previous step not confirmed")]
+    fn test_next_step_requires_confirmation() {
+        let (mut contract, _) = start_session_with_signed_withdraw();
+        set_context(OWNER);
+        contract.set_require_step_confirmations(true);
+        set_context(WORKER);
+
+        contract.assert_step_is_next(Step::CCTPApproveBurn);
+    }
+
+    #[test]
+    fn test_next_step_after_confirmation() {
+        let (mut contract, _) = start_session_with_signed_withdraw();
+        set_context(OWNER);
+        contract.set_require_step_confirmations(true);
+        set_context(WORKER);
+
+        contract.confirm_step(Step::AaveWithdraw, tx_hash(), 42, ExecutionStatus::Success);
+
+        contract.assert_step_is_next(Step::CCTPApproveBurn);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::errors::{ensure, ContractError};
+use crate::{types::Role, Contract, ContractExt};
+use near_sdk::{env, near, AccountId};
+
+#[near]
+impl Contract {
+    // @dev lets another worker continue the active session, e.g. after the original one went down
+    pub fn handover_session(&mut self, new_worker: AccountId) -> u64 {
+        self.require_role(Role::Operator);
+        ensure!(
+            self.active_session.is_some(),
+            ContractError::NoActiveSession
+        );
+        self.require_worker_has_valid_codehash(&new_worker);
+
+        let session = self.active_session.as_mut().unwrap();
+        let previous_worker = std::mem::replace(&mut session.worker, new_worker.clone());
+
+        env::log_str(&format!(
+            "Rebalance {} handed over from {} to {}",
+            session.nonce, previous_worker, new_worker
+        ));
+
+        session.nonce
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use near_sdk::AccountId;
+
+    const OTHER_WORKER: &str = "other-worker.testnet";
+
+    #[test]
+    fn test_handover_session() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        register_worker(&mut contract, OTHER_WORKER);
+
+        set_context(OWNER);
+        contract.handover_session(OTHER_WORKER.parse().unwrap());
+
+        let worker: AccountId = OTHER_WORKER.parse().unwrap();
+        assert_eq!(contract.get_active_session().worker, worker);
+
+        set_context(OTHER_WORKER);
+        contract.assert_session_worker_is_calling();
+    }
+
+    #[test]
+    #[should_panic(expected = "Session is bound to another worker")]
+    fn fails_if_other_worker_calls_step() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        register_worker(&mut contract, OTHER_WORKER);
+
+        set_context(OTHER_WORKER);
+        contract.assert_session_worker_is_calling();
+    }
+
+    #[test]
+    #[should_panic(expected = "Worker not registered")]
+    fn fails_to_hand_over_to_unregistered_worker() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+
+        set_context(OWNER);
+        contract.handover_session(OTHER_WORKER.parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic]
+    fn fails_if_not_owner() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        register_worker(&mut contract, OTHER_WORKER);
+
+        contract.handover_session(OTHER_WORKER.parse().unwrap());
+    }
+}
@@ -1,8 +1,13 @@
 pub mod aave_supply;
 pub mod aave_withdraw;
+pub mod abort_rebalance;
 pub mod cctp_burn;
 pub mod cctp_mint;
 pub mod complete_rebalance;
+pub mod confirm_step;
+pub mod handover_session;
+pub mod recover_session;
+pub mod resign_step;
 pub mod return_funds;
 pub mod start_rebalance;
 pub mod withdraw_for_crosschain_allocation;
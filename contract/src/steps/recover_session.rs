@@ -0,0 +1,216 @@
+use crate::errors::{ensure, ContractError};
+use crate::{
+    types::{RecoveryMode, Role, Step},
+    Contract, ContractExt,
+};
+use near_sdk::{env, near};
+
+#[near]
+impl Contract {
+    pub fn recover_session(&mut self, mode: RecoveryMode) -> u64 {
+        self.require_role(Role::Operator);
+        ensure!(
+            self.active_session.is_some(),
+            ContractError::NoActiveSession
+        );
+
+        if mode == RecoveryMode::Unwind {
+            let session = self.get_active_session();
+            let first_step = session.flow.sequence()[0];
+
+            ensure!(!session.unwinding, ContractError::SessionAlreadyUnwinding);
+            ensure!(
+                self.has_signature(first_step),
+                ContractError::NothingToUnwind
+            );
+            ensure!(
+                !self.has_signature(Step::CCTPBurn),
+                ContractError::FundsAlreadyBridged
+            );
+        }
+
+        let session = self.active_session.as_mut().unwrap();
+        session.started_at = env::block_timestamp_ms(); // @dev restarts the timeout window
+        if mode == RecoveryMode::Unwind {
+            session.unwinding = true;
+        }
+
+        env::log_str(&format!(
+            "Rebalance {} recovered with mode {:?}",
+            session.nonce, mode
+        ));
+
+        session.nonce
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use omni_transaction::evm::EVMTransaction;
+
+    #[test]
+    fn test_resume_restarts_timeout() {
+        set_context_with_timestamp(WORKER, 1_000);
+
+        let (mut contract, _) =
+            start_session_with_signed_steps(Flow::AaveToAave, &[Step::AaveWithdraw]);
+
+        let resumed_at = 1_000 + contract.session_timeout_ms + 1;
+        set_context_with_timestamp(OWNER, resumed_at);
+        assert!(contract.is_active_session_expired());
+
+        contract.recover_session(RecoveryMode::Resume);
+
+        assert!(!contract.is_active_session_expired());
+        assert_eq!(contract.get_active_session().started_at, resumed_at);
+        assert_eq!(contract.get_pending_step(), Some(Step::CCTPApproveBurn));
+    }
+
+    #[test]
+    fn test_unwind_switches_to_unwind_sequence() {
+        set_context(WORKER);
+
+        let (mut contract, _) =
+            start_session_with_signed_steps(Flow::AaveToAave, &[Step::AaveWithdraw]);
+
+        set_context(OWNER);
+        contract.recover_session(RecoveryMode::Unwind);
+
+        assert!(contract.get_active_session().unwinding);
+        assert_eq!(
+            contract.get_pending_step(),
+            Some(Step::RebalancerApproveDeposit)
+        );
+    }
+
+    fn sign_unwind_step(
+        contract: &mut crate::Contract,
+        nonce: u64,
+        step: Step,
+        tx: EVMTransaction,
+    ) {
+        contract.assert_step_is_next(step);
+        contract.assert_valid_partial_transaction(
+            &tx,
+            contract.get_chain_id_from_the_step_and_current_session(step),
+        );
+        contract
+            .signatures_by_nonce_and_type
+            .insert(CacheKey::new(nonce, step as u8), vec![step as u8]);
+    }
+
+    #[test]
+    fn test_unwind_on_vault_chain_completes_through_the_vault() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) =
+            start_session_with_signed_steps(Flow::AaveToAave, &[Step::AaveWithdraw]);
+
+        set_context(OWNER);
+        contract.recover_session(RecoveryMode::Unwind);
+
+        set_context(WORKER);
+        let mut partial_transaction = build_partial_tx();
+        partial_transaction.chain_id = DEFAULT_SOURCE_CHAIN;
+
+        let approve =
+            contract.prepare_approve_vault_to_manage_agents_usdc_tx(ApproveRebalancerDepositArgs {
+                amount: DEFAULT_AMOUNT,
+                partial_transaction: partial_transaction.clone(),
+            });
+        sign_unwind_step(
+            &mut contract,
+            nonce,
+            Step::RebalancerApproveDeposit,
+            approve,
+        );
+
+        let deposit = contract.prepare_return_funds_tx(RebalancerArgs {
+            amount: DEFAULT_AMOUNT,
+            partial_transaction,
+            cross_chain_a_token_balance: None,
+        });
+        sign_unwind_step(&mut contract, nonce, Step::RebalancerDeposit, deposit);
+
+        assert_eq!(contract.complete_rebalance(), nonce);
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Unwound
+        );
+    }
+
+    #[test]
+    fn test_unwind_on_other_chain_completes_through_aave() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        let third_chain = build_fake_configs().remove(1).with_chain_id("3");
+        contract.supported_chains.push(third_chain.chain_id);
+        contract
+            .config
+            .insert(third_chain.chain_id, third_chain.config);
+
+        let nonce = contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_DESTINATION_CHAIN,
+            third_chain.chain_id,
+            DEFAULT_AMOUNT,
+        );
+        contract
+            .signatures_by_nonce_and_type
+            .insert(CacheKey::new(nonce, Step::AaveWithdraw as u8), vec![0]);
+
+        set_context(OWNER);
+        contract.recover_session(RecoveryMode::Unwind);
+        assert_eq!(contract.get_pending_step(), Some(Step::AaveApproveSupply));
+
+        set_context(WORKER);
+        let mut partial_transaction = build_partial_tx();
+        partial_transaction.chain_id = DEFAULT_DESTINATION_CHAIN;
+
+        let approve = contract.prepare_aave_approve_supply_tx(ApproveAaveSupplyArgs {
+            amount: DEFAULT_AMOUNT,
+            partial_transaction: partial_transaction.clone(),
+        });
+        sign_unwind_step(&mut contract, nonce, Step::AaveApproveSupply, approve);
+
+        let supply = contract.prepare_aave_supply_tx(AaveArgs {
+            amount: DEFAULT_AMOUNT,
+            partial_transaction,
+        });
+        sign_unwind_step(&mut contract, nonce, Step::AaveSupply, supply);
+
+        assert_eq!(contract.complete_rebalance(), nonce);
+        assert_eq!(
+            contract.logs.get(&nonce).unwrap().status,
+            ActivityStatus::Unwound
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Funds already bridged, resume the session instead")]
+    fn fails_to_unwind_after_burn() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn, Step::CCTPBurn],
+        );
+
+        set_context(OWNER);
+        contract.recover_session(RecoveryMode::Unwind);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to unwind, abort the session instead")]
+    fn fails_to_unwind_before_withdraw() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+
+        set_context(OWNER);
+        contract.recover_session(RecoveryMode::Unwind);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::errors::{ensure, ContractError, OrContractError};
+use crate::{
+    constants::MIN_FEE_BUMP_BPS,
+    ecdsa,
+    external::this_contract,
+    types::{CacheKey, SignatureDeposit, Step},
+    Contract, ContractExt,
+};
+use near_sdk::{env, near, Gas, Promise};
+use omni_transaction::evm::EVMTransaction;
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+#[near]
+impl Contract {
+    // @dev re-signs an already signed step with the same calldata, nonce and gas limit but higher fees,
+    // only the fee fields of partial_transaction are used
+    #[payable]
+    pub fn resign_step(
+        &mut self,
+        step: Step,
+        partial_transaction: EVMTransaction,
+        callback_gas_tgas: u64,
+    ) -> Promise {
+        self.assert_session_worker_is_calling();
+        self.assert_session_not_expired();
+
+        let session = self.get_active_session();
+        self.assert_flow_not_paused(&session.flow);
+        self.assert_chain_not_paused(partial_transaction.chain_id);
+        ensure!(
+            self.session_sequence(session).contains(&step),
+            ContractError::StepNotInFlow
+        );
+
+        ensure!(
+            self.get_step_record(step)
+                .is_none_or(|record| record.confirmation.is_none()),
+            ContractError::StepAlreadyConfirmed
+        );
+
+        let nonce = session.nonce;
+        let key = CacheKey::new(nonce, step as u8);
+        let signed = self
+            .transactions_by_nonce_and_type
+            .get(&key)
+            .or_error(ContractError::StepNotSigned)
+            .clone();
+
+        ensure!(
+            partial_transaction.chain_id == signed.chain_id
+                && partial_transaction.nonce == signed.nonce,
+            ContractError::ReplacementNonceMismatch
+        );
+
+        // @dev each bump is relative to the latest signature, the ceiling to the first one so
+        // repeated re-signs cannot compound it
+        let (original_max_fee, original_priority_fee) = self
+            .original_fees_by_nonce_and_type
+            .get(&key)
+            .copied()
+            .unwrap_or((signed.max_fee_per_gas, signed.max_priority_fee_per_gas));
+        // @dev a zero tip has no relative ceiling, it stays bounded by the max fee like any EIP-1559 tip
+        let priority_fee_ceiling = if original_priority_fee == 0 {
+            partial_transaction.max_fee_per_gas
+        } else {
+            self.fee_bump_ceiling(original_priority_fee)
+        };
+        self.assert_fee_bump(
+            signed.max_fee_per_gas,
+            self.fee_bump_ceiling(original_max_fee),
+            partial_transaction.max_fee_per_gas,
+        );
+        self.assert_fee_bump(
+            signed.max_priority_fee_per_gas,
+            priority_fee_ceiling,
+            partial_transaction.max_priority_fee_per_gas,
+        );
+
+        let tx = signed.with_fees(
+            partial_transaction.max_fee_per_gas,
+            partial_transaction.max_priority_fee_per_gas,
+        );
+        let deposit = SignatureDeposit::collect(self.signer.signature_deposit);
+        self.reserve_evm_nonce(tx.chain_id, tx.nonce, true);
+
+        let payload_hash = self.hash_payload(&tx);
+
+        ecdsa::get_sig(payload_hash, &self.signer).then(
+            this_contract::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(callback_gas_tgas))
+                .sign_replacement_callback(nonce, step as u8, tx, deposit),
+        )
+    }
+}
+
+impl Contract {
+    fn fee_bump_ceiling(&self, original_fee: u128) -> u128 {
+        original_fee.saturating_mul(self.fee_bump_ceiling_bps) / BPS_DENOMINATOR
+    }
+
+    fn assert_fee_bump(&self, signed_fee: u128, max_fee: u128, new_fee: u128) {
+        let min_fee = signed_fee.saturating_mul(MIN_FEE_BUMP_BPS) / BPS_DENOMINATOR;
+        ensure!(new_fee >= min_fee, ContractError::FeeBumpTooLow);
+        ensure!(new_fee <= max_fee, ContractError::FeeBumpAboveCeiling);
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use crate::test_helpers::*;
+    use crate::types::*;
+    use omni_transaction::evm::EVMTransaction;
+
+    fn start_session_with_signed_withdraw() -> (crate::Contract, u64) {
+        let (mut contract, nonce) = start_session_with_signed_steps(Flow::AaveToAave, &[]);
+        let tx = build_partial_tx();
+        let key = CacheKey::new(nonce, Step::AaveWithdraw as u8);
+        contract
+            .transactions_by_nonce_and_type
+            .insert(key.clone(), TransactionParams::from(&tx));
+        contract
+            .original_fees_by_nonce_and_type
+            .insert(key, (tx.max_fee_per_gas, tx.max_priority_fee_per_gas));
+        contract
+            .next_evm_nonce_by_chain
+            .insert(tx.chain_id, tx.nonce + 1);
+        (contract, nonce)
+    }
+
+    // @dev what sign_replacement_callback leaves behind, the original fees stay untouched
+    fn record_signed_fees(
+        contract: &mut crate::Contract,
+        nonce: u64,
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    ) {
+        contract.transactions_by_nonce_and_type.insert(
+            CacheKey::new(nonce, Step::AaveWithdraw as u8),
+            TransactionParams::from(&bumped_tx(max_fee_per_gas, max_priority_fee_per_gas)),
+        );
+    }
+
+    fn bumped_tx(max_fee_per_gas: u128, max_priority_fee_per_gas: u128) -> EVMTransaction {
+        let mut tx = build_partial_tx();
+        tx.max_fee_per_gas = max_fee_per_gas;
+        tx.max_priority_fee_per_gas = max_priority_fee_per_gas;
+        tx
+    }
+
+    #[test]
+    fn test_resign_step() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_withdraw();
+
+        contract.resign_step(Step::AaveWithdraw, bumped_tx(150, 150), 50);
+
+        assert_eq!(
+            contract.get_next_nonce(DEFAULT_SOURCE_CHAIN),
+            Some(build_partial_tx().nonce + 1)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Step not signed yet")]
+    fn test_resign_step_fails_if_step_not_signed() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_withdraw();
+
+        contract.resign_step(Step::AaveSupply, bumped_tx(150, 150), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee bump too low")]
+    fn test_resign_step_fails_if_bump_too_low() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_withdraw();
+
+        contract.resign_step(Step::AaveWithdraw, bumped_tx(105, 150), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee bump above ceiling")]
+    fn test_resign_step_fails_if_bump_above_ceiling() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_withdraw();
+
+        contract.resign_step(Step::AaveWithdraw, bumped_tx(150, 301), 50);
+    }
+
+    #[test]
+    fn test_resign_step_bumps_a_replacement_up_to_the_original_ceiling() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) = start_session_with_signed_withdraw();
+        record_signed_fees(&mut contract, nonce, 250, 250);
+
+        contract.resign_step(Step::AaveWithdraw, bumped_tx(300, 300), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee bump above ceiling")]
+    fn test_resign_step_fails_if_replacements_compound_the_ceiling() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) = start_session_with_signed_withdraw();
+        record_signed_fees(&mut contract, nonce, 250, 250);
+
+        contract.resign_step(Step::AaveWithdraw, bumped_tx(330, 330), 50);
+    }
+
+    #[test]
+    fn test_resign_step_bumps_a_zero_priority_fee() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) = start_session_with_signed_withdraw();
+        record_signed_fees(&mut contract, nonce, 100, 0);
+        contract
+            .original_fees_by_nonce_and_type
+            .insert(CacheKey::new(nonce, Step::AaveWithdraw as u8), (100, 0));
+
+        contract.resign_step(Step::AaveWithdraw, bumped_tx(150, 20), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee bump above ceiling")]
+    fn test_resign_step_fails_if_zero_priority_fee_exceeds_max_fee() {
+        set_context(WORKER);
+
+        let (mut contract, nonce) = start_session_with_signed_withdraw();
+        record_signed_fees(&mut contract, nonce, 100, 0);
+        contract
+            .original_fees_by_nonce_and_type
+            .insert(CacheKey::new(nonce, Step::AaveWithdraw as u8), (100, 0));
+
+        contract.resign_step(Step::AaveWithdraw, bumped_tx(150, 151), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Replacement must reuse the original chain_id and nonce")]
+    fn test_resign_step_fails_if_nonce_changes() {
+        set_context(WORKER);
+
+        let (mut contract, _) = start_session_with_signed_withdraw();
+        let mut tx = bumped_tx(150, 150);
+        tx.nonce += 1;
+
+        contract.resign_step(Step::AaveWithdraw, tx, 50);
+    }
+}
@@ -1,32 +1,36 @@
-use std::str::FromStr;
-
 use crate::{
     tx_builders,
     types::{RebalancerArgs, Step},
     Contract, ContractExt,
 };
-use alloy_primitives::Address;
-use near_sdk::{near, Promise};
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_return_funds_tx(
         &mut self,
         args: RebalancerArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_return_funds_tx(args);
+
+        self.trigger_signature(Step::RebalancerDeposit, tx, callback_gas_tgas)
+    }
+}
+
+impl Contract {
+    pub(crate) fn prepare_return_funds_tx(&self, args: RebalancerArgs) -> EVMTransaction {
+        self.assert_step_amount(Step::RebalancerDeposit, args.amount);
+
         let cfg = self.get_chain_config_from_step_and_current_session(Step::RebalancerDeposit);
 
         let mut tx = args.clone().partial_transaction;
         tx.input = tx_builders::build_return_funds_tx(args);
-        tx.to = Some(
-            Address::from_str(&cfg.rebalancer.vault_address)
-                .expect("Invalid vault")
-                .into_array(),
-        );
-
-        self.trigger_signature(Step::RebalancerDeposit, tx, callback_gas_tgas)
+        tx.to = Some(cfg.rebalancer.vault_address.into_array());
+        tx
     }
 }
 
@@ -12,8 +12,12 @@ impl Contract {
     ) -> u64 {
         self.assert_no_active_session();
         self.assert_agent_is_calling();
-        self.is_chain_supported(&source_chain);
-        self.is_chain_supported(&destination_chain);
+        self.assert_chain_enabled(&source_chain);
+        self.assert_chain_enabled(&destination_chain);
+        self.assert_flow_matches_chains(&flow, source_chain, destination_chain);
+        self.assert_flow_not_paused(&flow);
+        self.assert_chain_not_paused(source_chain);
+        self.assert_chain_not_paused(destination_chain);
 
         let nonce = self.logs_nonce;
         self.logs_nonce += 1;
@@ -25,9 +29,15 @@ impl Contract {
                 source_chain: source_chain,
                 destination_chain,
                 transactions: vec![],
+                superseded_transactions: vec![],
                 timestamp: env::block_timestamp_ms(),
                 nonce,
                 amount,
+                status: ActivityStatus::Started,
+                steps: vec![],
+                skipped_steps: vec![],
+                failure_reason: None,
+                updated_at: env::block_timestamp_ms(),
             },
         );
 
@@ -35,6 +45,9 @@ impl Contract {
             nonce,
             flow,
             started_at: env::block_timestamp_ms(),
+            unwinding: false,
+            cctp_max_fee: None,
+            worker: env::predecessor_account_id(),
         });
 
         nonce
@@ -49,7 +62,9 @@ mod maintests {
 
     #[test]
     fn test_start_rebalance() {
-        let mut contract = init_contract_with_defaults();
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
 
         let flow = Flow::RebalancerToAave;
         let source_chain = DEFAULT_SOURCE_CHAIN;
@@ -68,6 +83,9 @@ mod maintests {
         assert_eq!(session.flow, flow);
         assert_eq!(session.nonce, 0);
         assert_eq!(session.started_at, env::block_timestamp_ms());
+        assert!(!session.unwinding);
+        assert!(session.cctp_max_fee.is_none());
+        assert_eq!(session.worker.as_str(), WORKER);
 
         let empty_vector: Vec<Vec<u8>> = vec![];
 
@@ -79,5 +97,193 @@ mod maintests {
         assert_eq!(log.timestamp, env::block_timestamp_ms());
         assert_eq!(log.nonce, 0);
         assert_eq!(log.amount, amount);
+        assert_eq!(log.status, ActivityStatus::Started);
+        assert!(log.steps.is_empty());
+        assert!(log.failure_reason.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Another action in progress")]
+    fn fails_if_session_is_active() {
+        set_context_with_timestamp(WORKER, 1_000);
+
+        let mut contract = init_contract_with_worker();
+        let amount: u128 = 1_000_000_000;
+
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            amount,
+        );
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            amount,
+        );
+    }
+
+    #[test]
+    fn test_start_rebalance_replaces_expired_session() {
+        set_context_with_timestamp(WORKER, 1_000);
+
+        let mut contract = init_contract_with_worker();
+        let amount: u128 = 1_000_000_000;
+
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            amount,
+        );
+
+        set_context_with_timestamp(WORKER, 1_000 + contract.session_timeout_ms + 1);
+
+        let nonce = contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            amount,
+        );
+
+        assert_eq!(nonce, 1);
+        assert_eq!(contract.get_active_session().nonce, 1);
+
+        let expired_log = contract.logs.get(&0).unwrap();
+        assert_eq!(expired_log.status, ActivityStatus::Aborted);
+        assert_eq!(
+            expired_log.failure_reason,
+            Some("Session expired".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Another action in progress")]
+    fn fails_to_replace_expired_session_after_burn() {
+        set_context_with_timestamp(WORKER, 1_000);
+
+        let (mut contract, _) = start_session_with_signed_steps(
+            Flow::AaveToAave,
+            &[Step::AaveWithdraw, Step::CCTPApproveBurn, Step::CCTPBurn],
+        );
+
+        set_context_with_timestamp(WORKER, 1_000 + contract.session_timeout_ms + 1);
+
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Worker not registered")]
+    fn fails_if_worker_not_registered() {
+        set_context("random.near");
+
+        let mut contract = init_contract_with_worker();
+
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Worker codehash not approved")]
+    fn fails_if_worker_codehash_not_approved() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        contract.approved_codehashes.clear();
+
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+    }
+
+    #[test]
+    fn test_start_rebalance_without_codehash_enforcement() {
+        set_context(OWNER);
+
+        let mut contract = init_contract_with_defaults();
+        contract.set_enforce_worker_codehash(false);
+
+        set_context("random.near");
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+
+        assert_eq!(contract.get_active_session().worker.as_str(), "random.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "Source and destination chains must differ")]
+    fn fails_if_source_is_destination() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Flow AaveToAave does not match the source and destination chains")]
+    fn fails_if_aave_destination_is_vault_chain() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        contract.start_rebalance(
+            Flow::AaveToAave,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Flow RebalancerToAave does not match the source and destination chains"
+    )]
+    fn fails_if_rebalancer_withdraw_is_not_on_vault_chain() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        contract.start_rebalance(
+            Flow::RebalancerToAave,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_AMOUNT,
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Flow AaveToRebalancer does not match the source and destination chains"
+    )]
+    fn fails_if_rebalancer_deposit_is_not_on_vault_chain() {
+        set_context(WORKER);
+
+        let mut contract = init_contract_with_worker();
+        contract.start_rebalance(
+            Flow::AaveToRebalancer,
+            DEFAULT_SOURCE_CHAIN,
+            DEFAULT_DESTINATION_CHAIN,
+            DEFAULT_AMOUNT,
+        );
     }
 }
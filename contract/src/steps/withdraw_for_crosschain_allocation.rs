@@ -1,33 +1,40 @@
-use std::str::FromStr;
-
 use crate::{
     tx_builders,
     types::{RebalancerArgs, Step},
     Contract, ContractExt,
 };
-use alloy_primitives::Address;
-use near_sdk::{near, Promise};
+use near_sdk::{near, PromiseOrValue};
+use omni_transaction::evm::EVMTransaction;
 
 #[near]
 impl Contract {
+    #[payable]
     pub fn build_and_sign_withdraw_for_crosschain_allocation_tx(
         &mut self,
         rebalancer_args: RebalancerArgs,
         callback_gas_tgas: u64,
-    ) -> Promise {
-        self.assert_agent_is_calling();
+    ) -> PromiseOrValue<Vec<u8>> {
+        self.assert_session_worker_is_calling();
+        let tx = self.prepare_withdraw_for_crosschain_allocation_tx(rebalancer_args);
+
+        self.trigger_signature(Step::RebalancerWithdrawToAllocate, tx, callback_gas_tgas)
+    }
+}
+
+impl Contract {
+    pub(crate) fn prepare_withdraw_for_crosschain_allocation_tx(
+        &self,
+        rebalancer_args: RebalancerArgs,
+    ) -> EVMTransaction {
+        self.assert_step_amount(Step::RebalancerWithdrawToAllocate, rebalancer_args.amount);
+
         let cfg =
             self.get_chain_config_from_step_and_current_session(Step::RebalancerWithdrawToAllocate);
 
         let mut tx = rebalancer_args.clone().partial_transaction;
         tx.input = tx_builders::build_withdraw_for_crosschain_allocation_tx(rebalancer_args);
-        tx.to = Some(
-            Address::from_str(&cfg.rebalancer.vault_address)
-                .expect("Invalid vault")
-                .into_array(),
-        );
-
-        self.trigger_signature(Step::RebalancerWithdrawToAllocate, tx, callback_gas_tgas)
+        tx.to = Some(cfg.rebalancer.vault_address.into_array());
+        tx
     }
 }
 
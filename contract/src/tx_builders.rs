@@ -1,13 +1,11 @@
 use crate::encoders;
+use crate::errors::{ContractError, OrContractError};
 use crate::types::{AaveArgs, AaveConfig, CCTPBurnArgs, CCTPMintArgs, RebalancerArgs};
 use alloy_primitives::{Address, B256, U256};
 use std::str::FromStr;
 
-pub fn build_cctp_approve_burn_tx(amount: u128, spender: String) -> Vec<u8> {
-    let input = encoders::cctp::usdc::encode_approve(
-        Address::from_str(&spender).expect("Invalid spender address"),
-        U256::from(amount),
-    );
+pub fn build_cctp_approve_burn_tx(amount: u128, spender: Address) -> Vec<u8> {
+    let input = encoders::cctp::usdc::encode_approve(spender, U256::from(amount));
     input
 }
 
@@ -15,9 +13,11 @@ pub fn build_cctp_burn_tx(args: CCTPBurnArgs) -> Vec<u8> {
     let input = encoders::cctp::messenger::encode_deposit_for_burn(
         U256::from(args.amount),
         args.destination_domain,
-        B256::from_str(&args.mint_recipient).expect("Invalid recipient"),
-        Address::from_str(&args.burn_token).expect("Invalid token address"),
-        B256::from_str(&args.destination_caller).expect("Invalid destination caller"),
+        B256::from_str(&args.mint_recipient).or_error(ContractError::InvalidAddress("recipient")),
+        Address::from_str(&args.burn_token)
+            .or_error(ContractError::InvalidAddress("token address")),
+        B256::from_str(&args.destination_caller)
+            .or_error(ContractError::InvalidAddress("destination caller")),
         U256::from(args.max_fee),
         args.min_finality_threshold,
     );
@@ -32,19 +32,16 @@ pub fn build_cctp_mint_tx(args: CCTPMintArgs) -> Vec<u8> {
     input
 }
 
-pub fn build_aave_approve_supply_tx(amount: u128, spender: String) -> Vec<u8> {
-    let input = encoders::cctp::usdc::encode_approve(
-        Address::from_str(&spender).expect("Invalid spender address"),
-        U256::from(amount),
-    );
+pub fn build_aave_approve_supply_tx(amount: u128, spender: Address) -> Vec<u8> {
+    let input = encoders::cctp::usdc::encode_approve(spender, U256::from(amount));
     input
 }
 
 pub fn build_aave_supply_tx(args: AaveArgs, config: AaveConfig) -> Vec<u8> {
     let input = encoders::aave::lending_pool::encode_supply(
-        Address::from_str(&config.asset).expect("Invalid asset address"),
+        config.asset.into(),
         U256::from(args.amount),
-        Address::from_str(&config.on_behalf_of).expect("Invalid on_behalf_of address"),
+        config.on_behalf_of.into(),
         config.referral_code,
     );
     input
@@ -52,9 +49,9 @@ pub fn build_aave_supply_tx(args: AaveArgs, config: AaveConfig) -> Vec<u8> {
 
 pub fn build_aave_withdraw_tx(args: AaveArgs, config: AaveConfig) -> Vec<u8> {
     let input = encoders::aave::lending_pool::encode_withdraw(
-        Address::from_str(&config.asset).expect("Invalid asset address"),
+        config.asset.into(),
         U256::from(args.amount),
-        Address::from_str(&config.on_behalf_of).expect("Invalid on_behalf_of address"),
+        config.on_behalf_of.into(),
     );
     input
 }
@@ -75,11 +72,8 @@ pub fn build_return_funds_tx(args: RebalancerArgs) -> Vec<u8> {
     input
 }
 
-pub fn build_approve_vault_to_manage_agents_usdc_tx(spender: String) -> Vec<u8> {
-    encoders::cctp::usdc::encode_approve(
-        Address::from_str(&spender).expect("Invalid spender address"),
-        U256::MAX,
-    )
+pub fn build_approve_vault_to_manage_agents_usdc_tx(amount: u128, spender: Address) -> Vec<u8> {
+    encoders::cctp::usdc::encode_approve(spender, U256::from(amount))
 }
 
 #[cfg(test)]
@@ -111,10 +105,10 @@ mod tests {
             partial_transaction: dummy_tx(),
         };
         let config = AaveConfig {
-            asset: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".to_string(),
-            on_behalf_of: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".to_string(),
+            asset: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".parse().unwrap(),
+            on_behalf_of: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".parse().unwrap(),
             referral_code: 0,
-            lending_pool_address: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".to_string(),
+            lending_pool_address: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".parse().unwrap(),
         };
         let payload = build_aave_supply_tx(args, config);
 
@@ -128,10 +122,10 @@ mod tests {
             partial_transaction: dummy_tx(),
         };
         let config = AaveConfig {
-            asset: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".to_string(),
-            on_behalf_of: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".to_string(),
+            asset: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".parse().unwrap(),
+            on_behalf_of: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".parse().unwrap(),
             referral_code: 0,
-            lending_pool_address: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".to_string(),
+            lending_pool_address: "87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".parse().unwrap(),
         };
         let payload = build_aave_withdraw_tx(args, config);
         assert!(!payload.is_empty());
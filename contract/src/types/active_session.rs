@@ -1,16 +1,17 @@
-use borsh::BorshSchema;
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
 use schemars::JsonSchema;
 
 use crate::types::Flow;
 
-#[derive(
-    Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, BorshSchema,
-)]
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ActiveSession {
     pub nonce: u64,
     pub flow: Flow,
     pub started_at: u64,
+    pub unwinding: bool, // @dev true once the session follows Flow::unwind_sequence
+    pub cctp_max_fee: Option<u128>, // @dev declared by the burn step, bounds the amount of later steps
+    pub worker: AccountId, // @dev only this worker can sign steps until the session ends or is handed over
 }
@@ -1,8 +1,9 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::env;
 use near_sdk::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 
-use crate::types::{AgentActionType, ChainId};
+use crate::types::{ActivityStatus, AgentActionType, ChainId, PayloadType, StepRecord};
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(crate = "near_sdk::serde")]
@@ -14,4 +15,18 @@ pub struct ActivityLog {
     pub nonce: u64,
     pub amount: u128,
     pub transactions: Vec<Vec<u8>>,
+    pub superseded_transactions: Vec<Vec<u8>>, // replaced by resign_step, same format as transactions
+    pub status: ActivityStatus,
+    pub steps: Vec<StepRecord>, // signing time of every step
+    pub skipped_steps: Vec<PayloadType>,
+    pub failure_reason: Option<String>,
+    pub updated_at: u64,
+}
+
+impl ActivityLog {
+    pub fn set_status(&mut self, status: ActivityStatus, failure_reason: Option<String>) {
+        self.status = status;
+        self.failure_reason = failure_reason;
+        self.updated_at = env::block_timestamp_ms();
+    }
 }
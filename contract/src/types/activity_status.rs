@@ -0,0 +1,28 @@
+use borsh::BorshSchema;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+#[derive(
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    BorshSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ActivityStatus {
+    Started,    // session opened, nothing signed yet
+    InProgress, // at least one step signed
+    Completed,
+    CompletedWithSkippedSteps, // closed by the owner before every step was signed
+    Aborted,                   // aborted by the owner or expired
+    Failed,                    // last signature request failed
+    Unwound,                   // funds returned to where the session took them from
+}
@@ -22,10 +22,10 @@ pub struct CCTPMintArgs {
 pub struct ApproveCctpBurnArgs {
     pub amount: u128,
     pub partial_transaction: EVMTransaction,
-    pub chain_id: u64,
 }
 
 pub type ApproveAaveSupplyArgs = ApproveCctpBurnArgs;
+pub type ApproveRebalancerDepositArgs = ApproveCctpBurnArgs;
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -0,0 +1,12 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use omni_transaction::evm::EVMTransaction;
+use schemars::JsonSchema;
+
+use crate::types::Step;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchEntry {
+    pub step: Step,
+    pub ethereum_tx: EVMTransaction,
+}
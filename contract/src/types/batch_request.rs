@@ -0,0 +1,22 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::types::{
+    AaveArgs, ApproveAaveSupplyArgs, ApproveCctpBurnArgs, ApproveRebalancerDepositArgs,
+    CCTPBurnArgs, CCTPMintArgs, RebalancerArgs,
+};
+
+// @dev one build_and_sign_* call per step, with the same args as its own entrypoint
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum BatchRequest {
+    AaveWithdraw(AaveArgs),
+    RebalancerWithdrawToAllocate(RebalancerArgs),
+    CCTPApproveBurn(ApproveCctpBurnArgs),
+    CCTPBurn(CCTPBurnArgs),
+    CCTPMint(CCTPMintArgs),
+    AaveApproveSupply(ApproveAaveSupplyArgs),
+    AaveSupply(AaveArgs),
+    RebalancerApproveDeposit(ApproveRebalancerDepositArgs),
+    RebalancerDeposit(RebalancerArgs),
+}
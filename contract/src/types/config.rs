@@ -2,7 +2,8 @@ use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 
-use crate::types::ChainId;
+use crate::errors::{ensure, ContractError};
+use crate::types::{ChainId, EvmAddress};
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
@@ -14,24 +15,24 @@ pub struct ChainConfig {
 #[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct AaveConfig {
-    pub asset: String,
-    pub on_behalf_of: String,
+    pub asset: EvmAddress,
+    pub on_behalf_of: EvmAddress,
     pub referral_code: u16,
-    pub lending_pool_address: String,
+    pub lending_pool_address: EvmAddress,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct CCTPConfig {
-    pub messenger_address: String,
-    pub transmitter_address: String,
-    pub usdc_address: String,
+    pub messenger_address: EvmAddress,
+    pub transmitter_address: EvmAddress,
+    pub usdc_address: EvmAddress,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct RebalancerConfig {
-    pub vault_address: String,
+    pub vault_address: EvmAddress,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
@@ -41,3 +42,29 @@ pub struct Config {
     pub cctp: CCTPConfig,
     pub rebalancer: RebalancerConfig,
 }
+
+impl Config {
+    // @dev only the source chain has a vault, the other chains hold funds in Aave
+    pub fn validate(&self, is_source_chain: bool) {
+        ensure!(
+            !self.aave.lending_pool_address.is_zero(),
+            ContractError::InvalidAddress("lending pool")
+        );
+        ensure!(
+            !self.cctp.messenger_address.is_zero(),
+            ContractError::InvalidAddress("messenger")
+        );
+        ensure!(
+            !self.cctp.transmitter_address.is_zero(),
+            ContractError::InvalidAddress("transmitter")
+        );
+        ensure!(
+            self.cctp.usdc_address == self.aave.asset,
+            ContractError::UsdcAssetMismatch
+        );
+        ensure!(
+            self.rebalancer.vault_address.is_zero() != is_source_chain,
+            ContractError::InvalidAddress("vault")
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+use schemars::JsonSchema;
+
+use crate::types::{ChainId, Config, ConfigUpdate};
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ConfigChange {
+    AddChain {
+        chain_id: ChainId,
+        config: Config,
+    },
+    UpdateChain {
+        chain_id: ChainId,
+        update: ConfigUpdate,
+    },
+    SetTimelock {
+        timelock_ms: u64,
+    },
+}
+
+impl ConfigChange {
+    pub fn chain_id(&self) -> Option<ChainId> {
+        match self {
+            ConfigChange::AddChain { chain_id, .. } => Some(*chain_id),
+            ConfigChange::UpdateChain { chain_id, .. } => Some(*chain_id),
+            ConfigChange::SetTimelock { .. } => None,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingConfigChange {
+    pub id: u64,
+    pub change: ConfigChange,
+    pub proposed_by: AccountId,
+    pub queued_at: u64,
+    pub executable_at: u64,
+}
@@ -0,0 +1,99 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::types::{AaveConfig, CCTPConfig, Config, EvmAddress, RebalancerConfig};
+
+// @dev every None field keeps its current value
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Clone, Default, Serialize, Deserialize, JsonSchema, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AaveConfigUpdate {
+    pub asset: Option<EvmAddress>,
+    pub on_behalf_of: Option<EvmAddress>,
+    pub referral_code: Option<u16>,
+    pub lending_pool_address: Option<EvmAddress>,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Clone, Default, Serialize, Deserialize, JsonSchema, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CCTPConfigUpdate {
+    pub messenger_address: Option<EvmAddress>,
+    pub transmitter_address: Option<EvmAddress>,
+    pub usdc_address: Option<EvmAddress>,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Clone, Default, Serialize, Deserialize, JsonSchema, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RebalancerConfigUpdate {
+    pub vault_address: Option<EvmAddress>,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Clone, Default, Serialize, Deserialize, JsonSchema, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigUpdate {
+    pub aave: Option<AaveConfigUpdate>,
+    pub cctp: Option<CCTPConfigUpdate>,
+    pub rebalancer: Option<RebalancerConfigUpdate>,
+}
+
+impl AaveConfig {
+    pub fn apply(&mut self, update: AaveConfigUpdate) {
+        if let Some(asset) = update.asset {
+            self.asset = asset;
+        }
+        if let Some(on_behalf_of) = update.on_behalf_of {
+            self.on_behalf_of = on_behalf_of;
+        }
+        if let Some(referral_code) = update.referral_code {
+            self.referral_code = referral_code;
+        }
+        if let Some(lending_pool_address) = update.lending_pool_address {
+            self.lending_pool_address = lending_pool_address;
+        }
+    }
+}
+
+impl CCTPConfig {
+    pub fn apply(&mut self, update: CCTPConfigUpdate) {
+        if let Some(messenger_address) = update.messenger_address {
+            self.messenger_address = messenger_address;
+        }
+        if let Some(transmitter_address) = update.transmitter_address {
+            self.transmitter_address = transmitter_address;
+        }
+        if let Some(usdc_address) = update.usdc_address {
+            self.usdc_address = usdc_address;
+        }
+    }
+}
+
+impl RebalancerConfig {
+    pub fn apply(&mut self, update: RebalancerConfigUpdate) {
+        if let Some(vault_address) = update.vault_address {
+            self.vault_address = vault_address;
+        }
+    }
+}
+
+impl Config {
+    pub fn apply(&mut self, update: ConfigUpdate) {
+        if let Some(aave) = update.aave {
+            self.aave.apply(aave);
+        }
+        if let Some(cctp) = update.cctp {
+            self.cctp.apply(cctp);
+        }
+        if let Some(rebalancer) = update.rebalancer {
+            self.rebalancer.apply(rebalancer);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use alloy_primitives::Address;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use std::fmt;
+use std::str::FromStr;
+
+// @dev serialized as a 0x-prefixed EIP-55 checksummed string
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct EvmAddress(pub [u8; 20]);
+
+impl EvmAddress {
+    pub const ZERO: Self = Self([0; 20]);
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    pub fn into_array(self) -> [u8; 20] {
+        self.0
+    }
+}
+
+impl FromStr for EvmAddress {
+    type Err = &'static str;
+
+    // @dev all-lowercase or all-uppercase input carries no checksum, mixed case must be valid EIP-55
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let hex = value.strip_prefix("0x").unwrap_or(value);
+        let address = Address::from_str(hex).map_err(|_| "invalid EVM address")?;
+
+        let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase())
+            && hex.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_checksum(None)[2..] != *hex {
+            return Err("invalid EVM address checksum");
+        }
+
+        Ok(Self(address.into_array()))
+    }
+}
+
+impl From<EvmAddress> for Address {
+    fn from(value: EvmAddress) -> Self {
+        Address::from(value.0)
+    }
+}
+
+impl From<Address> for EvmAddress {
+    fn from(value: Address) -> Self {
+        Self(value.into_array())
+    }
+}
+
+impl fmt::Display for EvmAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Address::from(*self).to_checksum(None))
+    }
+}
+
+impl Serialize for EvmAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EvmAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value
+            .parse()
+            .map_err(|err| de::Error::custom(format!("{}: {}", err, value)))
+    }
+}
+
+impl JsonSchema for EvmAddress {
+    fn schema_name() -> String {
+        "EvmAddress".to_string()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        String::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod maintests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accepts_single_case_and_valid_checksum() {
+        let lower: EvmAddress = "0xe737e5cebeeba77efe34d4aa090756590b1ce275"
+            .parse()
+            .unwrap();
+        let checksummed: EvmAddress = "0xE737e5cEBEEBa77EFE34D4aa090756590b1CE275"
+            .parse()
+            .unwrap();
+
+        assert_eq!(lower, checksummed);
+        assert_eq!(
+            lower.to_string(),
+            "0xE737e5cEBEEBa77EFE34D4aa090756590b1CE275"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_checksum() {
+        assert_eq!(
+            "0xe737e5cEBEEBa77EFE34D4aa090756590b1CE275".parse::<EvmAddress>(),
+            Err("invalid EVM address checksum")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_wrong_length() {
+        assert!("0x1234".parse::<EvmAddress>().is_err());
+        assert!("".parse::<EvmAddress>().is_err());
+    }
+}
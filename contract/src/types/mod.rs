@@ -1,21 +1,57 @@
 mod active_session;
 mod activity_log;
+mod activity_status;
 mod agent_action;
 mod args;
+mod batch_entry;
+mod batch_request;
 mod cache_key;
 mod config;
+mod config_change;
+mod config_update;
+mod evm_address;
+mod execution_status;
 mod flow;
+mod owner_proposal;
+mod pause_status;
 mod payload_type;
+mod recovery_mode;
+mod role;
+mod sign_request_format;
+mod signature_deposit;
+mod signature_domain;
+mod signer_config;
+mod step_confirmation;
+mod step_record;
+mod transaction_params;
 mod worker;
 
 pub use active_session::*;
 pub use activity_log::*;
+pub use activity_status::*;
 pub use agent_action::*;
 pub use args::*;
+pub use batch_entry::*;
+pub use batch_request::*;
 pub use cache_key::*;
 pub use config::*;
+pub use config_change::*;
+pub use config_update::*;
+pub use evm_address::*;
+pub use execution_status::*;
 pub use flow::*;
+pub use owner_proposal::*;
+pub use pause_status::*;
 pub use payload_type::*;
+pub use recovery_mode::*;
+pub use role::*;
+pub use sign_request_format::*;
+pub use signature_deposit::*;
+pub use signature_domain::*;
+pub use signer_config::*;
+pub use step_confirmation::*;
+pub use step_record::*;
+pub use transaction_params::*;
 pub use worker::*;
 
 pub type Step = PayloadType;
@@ -0,0 +1,13 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+use schemars::JsonSchema;
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnerProposal {
+    pub account_id: AccountId,
+    pub proposed_at: u64,
+    pub accept_after: u64, // @dev optional timelock, equal to proposed_at when none was requested
+    pub expires_at: u64,
+}
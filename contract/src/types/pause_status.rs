@@ -0,0 +1,12 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::types::{ChainId, Flow};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseStatus {
+    pub paused: bool, // @dev halts every signature regardless of chain and flow
+    pub paused_chains: Vec<ChainId>,
+    pub paused_flows: Vec<Flow>,
+}
@@ -29,6 +29,9 @@ pub enum PayloadType {
     RebalancerUpdateCrossChainBalance = 5,
     RebalancerDeposit = 6,
     RebalancerSignCrossChainBalance = 7,
+    CCTPApproveBurn = 8,
+    AaveApproveSupply = 9,
+    RebalancerApproveDeposit = 10,
 }
 
 impl From<u8> for PayloadType {
@@ -42,6 +45,9 @@ impl From<u8> for PayloadType {
             5 => PayloadType::RebalancerUpdateCrossChainBalance,
             6 => PayloadType::RebalancerDeposit,
             7 => PayloadType::RebalancerSignCrossChainBalance,
+            8 => PayloadType::CCTPApproveBurn,
+            9 => PayloadType::AaveApproveSupply,
+            10 => PayloadType::RebalancerApproveDeposit,
             _ => panic!("Unknown PayloadType: {}", value),
         }
     }
@@ -0,0 +1,23 @@
+use borsh::BorshSchema;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+#[derive(
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    BorshSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RecoveryMode {
+    Resume, // continue from get_pending_step
+    Unwind, // return the withdrawn funds to where they came from on the source chain
+}
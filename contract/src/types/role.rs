@@ -0,0 +1,24 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    Operator,         // aborts, recovers and hands over sessions
+    Guardian,         // pauses signing
+    ConfigManager,    // edits chain configs and contract parameters
+    CodehashApprover, // approves worker codehashes
+}
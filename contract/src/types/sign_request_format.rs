@@ -0,0 +1,38 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::types::SignatureDomain;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Clone, PartialEq, Serialize, Deserialize, JsonSchema, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SignRequestFormat {
+    Legacy, // sign({ request: { payload, path, key_version } }), ECDSA only
+    V2 {
+        // sign({ request: { payload_v2, path, domain_id } })
+        ecdsa_domain_id: u64,
+        eddsa_domain_id: u64,
+    },
+}
+
+impl SignRequestFormat {
+    pub fn domain_id(&self, domain: SignatureDomain) -> Option<u64> {
+        match (self, domain) {
+            (SignRequestFormat::Legacy, _) => None,
+            (
+                SignRequestFormat::V2 {
+                    ecdsa_domain_id, ..
+                },
+                SignatureDomain::Ecdsa,
+            ) => Some(*ecdsa_domain_id),
+            (
+                SignRequestFormat::V2 {
+                    eddsa_domain_id, ..
+                },
+                SignatureDomain::Eddsa,
+            ) => Some(*eddsa_domain_id),
+        }
+    }
+}
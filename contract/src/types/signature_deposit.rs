@@ -0,0 +1,50 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, AccountId, NearToken, Promise};
+use schemars::JsonSchema;
+
+use crate::errors::{ensure, ContractError};
+
+// @dev what the caller of a signing endpoint attached, settled by the signature callback
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignatureDeposit {
+    pub payer: AccountId,
+    #[schemars(with = "String")]
+    pub attached: NearToken,
+    #[schemars(with = "String")]
+    pub required: NearToken,
+}
+
+impl SignatureDeposit {
+    pub fn collect(required: NearToken) -> Self {
+        let attached = env::attached_deposit();
+        ensure!(
+            attached >= required,
+            ContractError::InsufficientSignatureDeposit
+        );
+
+        Self {
+            payer: env::predecessor_account_id(),
+            attached,
+            required,
+        }
+    }
+
+    // @dev a failed sign call returns its deposit to this contract, so the payer gets all of it back
+    pub fn refund(&self, mpc_call_succeeded: bool) {
+        self.refund_unspent(mpc_call_succeeded as u128, 1);
+    }
+
+    // @dev `required` covers `requested` sign calls of equal deposit, only the succeeded ones are spent
+    pub fn refund_unspent(&self, succeeded: u128, requested: u128) {
+        let spent = self
+            .required
+            .saturating_mul(succeeded)
+            .saturating_div(requested);
+        let amount = self.attached.saturating_sub(spent);
+
+        if !amount.is_zero() {
+            Promise::new(self.payer.clone()).transfer(amount);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+#[derive(
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SignatureDomain {
+    Ecdsa, // secp256k1, EVM chains
+    Eddsa, // ed25519, for non-EVM targets
+}
@@ -0,0 +1,45 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, NearToken, PublicKey};
+use schemars::JsonSchema;
+
+use crate::types::SignRequestFormat;
+
+use crate::constants::{
+    DEFAULT_KEY_VERSION, DEFAULT_PATH, DEFAULT_SIGNATURE_DEPOSIT, MPC_CONTRACT_ACCOUNT_ID,
+    MPC_CONTRACT_ACCOUNT_ID_TESTNET, MPC_ROOT_PUBLIC_KEY, MPC_ROOT_PUBLIC_KEY_TESTNET,
+};
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Clone, PartialEq, Serialize, Deserialize, JsonSchema, Debug,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignerConfig {
+    pub mpc_contract_id: AccountId,
+    pub path: String,
+    pub key_version: u32,
+    pub root_public_key: PublicKey, // @dev root key of the MPC network, the agent key is derived from it
+    pub request_format: SignRequestFormat,
+    #[schemars(with = "String")]
+    pub signature_deposit: NearToken, // @dev what the MPC contract requires per request, paid by the caller. See sync_signature_deposit
+}
+
+impl SignerConfig {
+    // @dev the signer that was picked from the account name before it became configurable
+    pub fn default_for(account_id: &AccountId) -> Self {
+        let (mpc_contract_id, root_public_key) = if account_id.as_str().contains("testnet") {
+            (MPC_CONTRACT_ACCOUNT_ID_TESTNET, MPC_ROOT_PUBLIC_KEY_TESTNET)
+        } else {
+            (MPC_CONTRACT_ACCOUNT_ID, MPC_ROOT_PUBLIC_KEY)
+        };
+
+        Self {
+            mpc_contract_id: mpc_contract_id.parse().unwrap(),
+            path: DEFAULT_PATH.to_string(),
+            key_version: DEFAULT_KEY_VERSION,
+            root_public_key: root_public_key.parse().unwrap(),
+            request_format: SignRequestFormat::Legacy,
+            signature_deposit: DEFAULT_SIGNATURE_DEPOSIT,
+        }
+    }
+}
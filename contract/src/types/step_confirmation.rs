@@ -0,0 +1,14 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::types::ExecutionStatus;
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StepConfirmation {
+    pub tx_hash: String, // 0x-prefixed hash of the broadcast transaction
+    pub block_number: u64,
+    pub status: ExecutionStatus,
+    pub confirmed_at: u64,
+}
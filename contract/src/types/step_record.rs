@@ -0,0 +1,15 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::types::{ChainId, PayloadType, StepConfirmation};
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StepRecord {
+    pub step: PayloadType,
+    pub signed_at: u64,
+    pub chain_id: ChainId,
+    pub evm_nonce: u64,
+    pub confirmation: Option<StepConfirmation>, // set by confirm_step once the tx landed
+}
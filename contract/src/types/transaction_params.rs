@@ -0,0 +1,48 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use omni_transaction::evm::EVMTransaction;
+
+// @dev fields of a signed EVM transaction, kept to re-sign it with higher fees
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct TransactionParams {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub to: Option<[u8; 20]>,
+    pub input: Vec<u8>,
+    pub gas_limit: u128,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+impl From<&EVMTransaction> for TransactionParams {
+    fn from(tx: &EVMTransaction) -> Self {
+        Self {
+            chain_id: tx.chain_id,
+            nonce: tx.nonce,
+            to: tx.to,
+            input: tx.input.clone(),
+            gas_limit: tx.gas_limit,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+        }
+    }
+}
+
+impl TransactionParams {
+    pub fn with_fees(
+        &self,
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    ) -> EVMTransaction {
+        EVMTransaction {
+            chain_id: self.chain_id,
+            nonce: self.nonce,
+            to: self.to,
+            value: 0,
+            input: self.input.clone(),
+            gas_limit: self.gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            access_list: vec![],
+        }
+    }
+}
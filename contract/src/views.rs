@@ -1,10 +1,14 @@
 use alloy_primitives::{Address, B256, U256};
-use near_sdk::{near, AccountId};
+use near_sdk::{env, near, AccountId};
 use std::str::FromStr;
 
+use crate::errors::{is_retryable_code, ContractError, OrContractError};
 use crate::{
-    encoders, tx_builders,
-    types::{ActiveSession, ActivityLog, CacheKey, ChainId, Config, Flow, Step, Worker},
+    derivation, encoders, tx_builders,
+    types::{
+        ActiveSession, ActivityLog, CacheKey, ChainId, Config, EvmAddress, Flow, SignerConfig,
+        Step, Worker,
+    },
     Contract, ContractExt,
 };
 
@@ -18,6 +22,10 @@ impl Contract {
         self.supported_chains.clone()
     }
 
+    pub fn get_disabled_chains(&self) -> Vec<ChainId> {
+        self.disabled_chains.iter().copied().collect()
+    }
+
     pub fn get_all_configs(&self) -> Vec<(ChainId, Config)> {
         self.supported_chains
             .iter()
@@ -33,7 +41,7 @@ impl Contract {
         self.worker_by_account_id
             .get(&account_id)
             .cloned()
-            .expect("Worker not registered")
+            .or_error(ContractError::WorkerNotRegistered)
     }
 
     pub fn get_latest_logs(&self, count: u64) -> Vec<ActivityLog> {
@@ -49,6 +57,8 @@ impl Contract {
         for nonce in (start..current_nonce).rev() {
             if let Some(log) = self.logs.get(&nonce) {
                 logs.push(log.clone());
+            } else if let Some(log) = self.v0_logs.get(&nonce) {
+                logs.push(log.clone().migrate());
             }
         }
 
@@ -58,17 +68,20 @@ impl Contract {
     pub fn get_chain_config(&self, destination_chain: &ChainId) -> &Config {
         self.config
             .get(destination_chain)
-            .expect("Chain not configured")
+            .or_error(ContractError::ChainNotConfigured)
     }
 
     pub fn get_active_session(&self) -> &ActiveSession {
-        self.active_session.as_ref().expect("No active session")
+        self.active_session
+            .as_ref()
+            .or_error(ContractError::NoActiveSession)
     }
 
     pub fn get_signed_transactions(&self, nonce: u64) -> Vec<Vec<u8>> {
         self.logs
             .get(&nonce)
             .map(|log| log.transactions.clone())
+            .or_else(|| self.v0_logs.get(&nonce).map(|log| log.transactions.clone()))
             .unwrap_or_default()
     }
 
@@ -79,13 +92,16 @@ impl Contract {
 
     pub fn get_activity_log(&self) -> ActivityLog {
         let nonce = self.get_active_session().nonce;
-        self.logs.get(&nonce).expect("Log not found").clone()
+        self.logs
+            .get(&nonce)
+            .or_error(ContractError::LogNotFound)
+            .clone()
     }
 
     // Transaction Input Builders
     pub fn build_cctp_approve_before_burn_tx(&self, spender: String, amount: u128) -> Vec<u8> {
         encoders::cctp::usdc::encode_approve(
-            Address::from_str(&spender).expect("Invalid spender address"),
+            Address::from_str(&spender).or_error(ContractError::InvalidAddress("spender address")),
             U256::from(amount),
         )
     }
@@ -103,9 +119,10 @@ impl Contract {
         encoders::cctp::messenger::encode_deposit_for_burn(
             U256::from(amount),
             destination_domain,
-            B256::from_str(&mint_recipient).expect("Invalid recipient"),
-            Address::from_str(&burn_token).expect("Invalid token address"),
-            B256::from_str(&destination_caller).expect("Invalid destination caller"),
+            B256::from_str(&mint_recipient).or_error(ContractError::InvalidAddress("recipient")),
+            Address::from_str(&burn_token).or_error(ContractError::InvalidAddress("token address")),
+            B256::from_str(&destination_caller)
+                .or_error(ContractError::InvalidAddress("destination caller")),
             U256::from(max_fee),
             min_finality_threshold,
         )
@@ -117,7 +134,7 @@ impl Contract {
 
     pub fn build_aave_approve_before_supply_tx(&self, spender: String, amount: u128) -> Vec<u8> {
         encoders::cctp::usdc::encode_approve(
-            Address::from_str(&spender).expect("Invalid spender address"),
+            Address::from_str(&spender).or_error(ContractError::InvalidAddress("spender address")),
             U256::from(amount),
         )
     }
@@ -130,9 +147,10 @@ impl Contract {
         referral_code: u16,
     ) -> Vec<u8> {
         encoders::aave::lending_pool::encode_supply(
-            Address::from_str(&asset).expect("Invalid asset address"),
+            Address::from_str(&asset).or_error(ContractError::InvalidAddress("asset address")),
             U256::from(amount),
-            Address::from_str(&on_behalf_of).expect("Invalid on_behalf_of address"),
+            Address::from_str(&on_behalf_of)
+                .or_error(ContractError::InvalidAddress("on_behalf_of address")),
             referral_code,
         )
     }
@@ -144,9 +162,10 @@ impl Contract {
         on_behalf_of: String,
     ) -> Vec<u8> {
         encoders::aave::lending_pool::encode_withdraw(
-            Address::from_str(&asset).expect("Invalid asset address"),
+            Address::from_str(&asset).or_error(ContractError::InvalidAddress("asset address")),
             U256::from(amount),
-            Address::from_str(&on_behalf_of).expect("Invalid on_behalf_of address"),
+            Address::from_str(&on_behalf_of)
+                .or_error(ContractError::InvalidAddress("on_behalf_of address")),
         )
     }
 
@@ -172,13 +191,64 @@ impl Contract {
         )
     }
 
-    pub fn build_approve_vault_to_manage_agents_usdc(&self, spender: String) -> Vec<u8> {
-        tx_builders::build_approve_vault_to_manage_agents_usdc_tx(spender)
+    pub fn build_approve_vault_to_manage_agents_usdc(
+        &self,
+        spender: String,
+        amount: u128,
+    ) -> Vec<u8> {
+        tx_builders::build_approve_vault_to_manage_agents_usdc_tx(
+            amount,
+            Address::from_str(&spender).or_error(ContractError::InvalidAddress("spender address")),
+        )
+    }
+
+    pub fn get_next_nonce(&self, chain_id: ChainId) -> Option<u64> {
+        self.next_evm_nonce_by_chain.get(&chain_id).copied()
+    }
+
+    pub fn get_signer_config(&self) -> SignerConfig {
+        self.signer.clone()
+    }
+
+    // @dev the address the MPC network signs for when this contract requests a signature
+    pub fn get_agent_address(&self) -> EvmAddress {
+        derivation::derive_evm_address(
+            &self.signer.root_public_key,
+            &env::current_account_id(),
+            &self.signer.path,
+        )
+    }
+
+    pub fn get_fee_bump_ceiling(&self) -> u128 {
+        self.fee_bump_ceiling_bps
+    }
+
+    pub fn get_require_step_confirmations(&self) -> bool {
+        self.require_step_confirmations
+    }
+
+    pub fn is_worker_codehash_enforced(&self) -> bool {
+        self.enforce_worker_codehash
+    }
+
+    // @dev lets the agent decide whether to retry a failed call from its "E<code>" prefix
+    pub fn is_retryable_error(&self, code: u16) -> bool {
+        is_retryable_code(code)
+    }
+
+    pub fn get_session_timeout(&self) -> u64 {
+        self.session_timeout_ms
+    }
+
+    pub fn is_active_session_expired(&self) -> bool {
+        self.active_session
+            .as_ref()
+            .is_some_and(|session| self.is_session_expired(session))
     }
 
     pub fn get_pending_step(&self) -> Option<Step> {
         if let Some(session) = &self.active_session {
-            for &st in session.flow.sequence() {
+            for &st in self.session_sequence(session) {
                 if !self.has_signature(st) {
                     return Some(st);
                 }
@@ -192,7 +262,7 @@ impl Contract {
     pub fn get_active_session_info(&self) -> Option<(u64, Flow, Option<Step>)> {
         if let Some(session) = &self.active_session {
             let mut pending = None;
-            for &st in session.flow.sequence() {
+            for &st in self.session_sequence(session) {
                 if !self.has_signature(st) {
                     pending = Some(st);
                     break;
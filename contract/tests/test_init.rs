@@ -24,6 +24,7 @@ struct Config {
     path: String,
     cctp_contracts: serde_json::Map<String, Value>,
     aave_contracts: serde_json::Map<String, Value>,
+    vault_address: String,
 }
 
 async fn deploy_and_initialise(
@@ -58,7 +59,8 @@ async fn deploy_and_initialise(
                         "usdc_address": cctp.get("usdc").map(|v| v.as_str().unwrap()).unwrap_or(zero_address)
                     },
                     "rebalancer": {
-                        "vault_address": zero_address
+                        // @dev only the source chain has a vault
+                        "vault_address": if chain_id == config.source_chain { config.vault_address.as_str() } else { zero_address }
                     }
                 }
             })
@@ -42,6 +42,7 @@ const VAULT_ADDRESS_ARBITRUM_SEPOLIA: &str = "0xcEc84a8e4000Dc7d2B64bbbe9fD3559A
 const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
 const SOURCE_CHAIN_ID: u64 = ARBITRUM_CHAIN_ID_SEPOLIA;
 const PATH: &str = "ethereum-1";
+const SIGNATURE_DEPOSIT: u128 = 500_000_000_000_000_000_000_000; // @dev covers the MPC deposit, the excess is refunded
 
 async fn deploy_and_initialise(
     deployer_account: NearAccount,
@@ -69,6 +70,7 @@ async fn deploy_and_initialise(
                 "cctp": {
                     "messenger_address": MESSENGER_ADDRESS_ARBITRUM_SEPOLIA, // CCTP Messenger on Arbitrum Sepolia
                     "transmitter_address": TRANSMITTER_ADDRESS_ARBITRUM_SEPOLIA, // CCTP Transmitter on Arbitrum Sepolia
+                    "usdc_address": USDC_ARBITRUM_SEPOLIA,
                 },
                 "rebalancer": {
                     "vault_address": VAULT_ADDRESS_ARBITRUM_SEPOLIA
@@ -87,6 +89,7 @@ async fn deploy_and_initialise(
                 "cctp": {
                     "messenger_address": MESSENGER_ADDRESS_OPTIMISM_SEPOLIA, // CCTP Messenger on Optimism Sepolia
                     "transmitter_address": TRANSMITTER_ADDRESS_OPTIMISM_SEPOLIA, // CCTP Transmitter on Optimism Sepolia
+                    "usdc_address": USDC_OPTIMISM_SEPOLIA,
                 },
                 "rebalancer": {
                     "vault_address": ZERO_ADDRESS
@@ -149,7 +152,7 @@ async fn burn_for_bridge(
             method_name: "build_and_sign_cctp_burn_tx".to_string(),
             args: burn_for_bridge_args.to_string().into_bytes(), // Convert directly to Vec<u8>
             gas: 300000000000000,
-            deposit: 0,
+            deposit: SIGNATURE_DEPOSIT,
         })
         .await?;
     println!("Burn for bridge result: {:?}", burn_for_bridge_result);
@@ -212,7 +215,7 @@ async fn withdraw_funds_for_allocation(
             method_name: "build_and_sign_withdraw_for_crosschain_allocation_tx".to_string(),
             args: withdraw_for_allocation_args.to_string().into_bytes(), // Convert directly to Vec<u8>
             gas: 300000000000000,
-            deposit: 0,
+            deposit: SIGNATURE_DEPOSIT,
         })
         .await?;
 
@@ -224,6 +227,41 @@ async fn withdraw_funds_for_allocation(
     Ok(())
 }
 
+async fn approve_before_burn(
+    deployer_account: NearAccount,
+    rpc_url: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let provider = ProviderBuilder::new().on_anvil_with_config(|anvil| anvil.fork(rpc_url.clone()));
+    let agent_address = get_agent_address(deployer_account.clone());
+
+    let friendly_json_rpc_client =
+        FriendlyNearJsonRpcClient::new(NearNetworkConfig::Testnet, deployer_account.clone());
+
+    // Approve the messenger to burn the session amount on the source chain
+    let approve_before_burn_args = json!({
+        "args": {
+            "amount": USDC_AMOUNT,
+            "partial_transaction": build_transaction(&provider, agent_address).await?
+        },
+        "callback_gas_tgas": 10
+    });
+
+    let approve_before_burn_result = friendly_json_rpc_client
+        .send_action(FunctionCallAction {
+            method_name: "build_and_sign_cctp_approve_burn_tx".to_string(),
+            args: approve_before_burn_args.to_string().into_bytes(), // Convert directly to Vec<u8>
+            gas: 300000000000000,
+            deposit: SIGNATURE_DEPOSIT,
+        })
+        .await?;
+    println!(
+        "Approve before burn result: {:?}",
+        approve_before_burn_result
+    );
+
+    Ok(())
+}
+
 // async fn get_attestation_from_circle_api() -> Result<(), Box<dyn std::error::Error>> {}
 
 async fn mint_for_bridge(
@@ -251,7 +289,7 @@ async fn mint_for_bridge(
             method_name: "build_and_sign_cctp_mint_tx".to_string(),
             args: mint_for_bridge_args.to_string().into_bytes(), // Convert directly to Vec<u8>
             gas: 300000000000000,
-            deposit: 0,
+            deposit: SIGNATURE_DEPOSIT,
         })
         .await?;
 
@@ -286,7 +324,7 @@ async fn aave_supply(
             method_name: "build_and_sign_aave_supply_tx".to_string(),
             args: deposit_to_aave_args.to_string().into_bytes(), // Convert directly to Vec<u8>
             gas: 300000000000000,
-            deposit: 0,
+            deposit: SIGNATURE_DEPOSIT,
         })
         .await?;
 
@@ -454,6 +492,7 @@ async fn full_flow_test() -> Result<(), Box<dyn std::error::Error>> {
     deploy_and_initialise(deployer_account.clone()).await?;
     // start_rebalance(deployer_account.clone()).await?;
     // withdraw_funds_for_allocation(deployer_account.clone(), alchemy_url.clone()).await?;
+    // approve_before_burn(deployer_account.clone(), alchemy_url.clone()).await?;
     // burn_for_bridge(deployer_account.clone(), alchemy_url.clone()).await?;
     // test_get_activity().await?;
     // test_get_allocations().await?;